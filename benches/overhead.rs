@@ -0,0 +1,110 @@
+//! Time per op of `Ft!(Fr)` compared to the raw field, also when the ops of
+//! `Ft!(Fr)` and `Ft!(Fq)` alternate, run with `cargo bench --bench overhead`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ark_bn254::{Fq, Fr};
+use ark_ff::{Field, UniformRand};
+use field_tracker::tracker::{update_add, update_field, Op, Tracker};
+use field_tracker::{end_tscope, start_tscope, Ft};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+type F = Ft!(Fr);
+type G = Ft!(Fq);
+
+const OPS: u32 = 1 << 22;
+
+fn time(f: impl Fn() -> u32) -> Duration {
+    // best of a few runs to leave out warm up and scheduling noise
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn mul_chain<T: Field>(seed: T) -> u32 {
+    let mut acc = seed;
+    for _ in 0..OPS {
+        acc *= seed;
+    }
+    black_box(acc);
+    OPS
+}
+
+fn mixed_chain<A: Field, B: Field>(a: A, b: B) -> u32 {
+    let (mut x, mut y) = (a, b);
+    for _ in 0..OPS / 2 {
+        x *= a;
+        y *= b;
+    }
+    black_box((x, y));
+    OPS
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:<24} {:>8.2} ns/op",
+        name,
+        elapsed.as_nanos() as f64 / OPS as f64
+    );
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let raw_seed = Fr::rand(&mut rng);
+    let tracked_seed = F::rand(&mut rng);
+
+    let raw = time(|| mul_chain(black_box(raw_seed)));
+    report("Fr mul", raw);
+    let raw_mixed_seed = Fq::rand(&mut rng);
+    let raw_mixed = time(|| mixed_chain(black_box(raw_seed), black_box(raw_mixed_seed)));
+    report("Fr, Fq mul", raw_mixed);
+
+    start_tscope!("bench");
+    let tracked = time(|| mul_chain(black_box(tracked_seed)));
+    report("Ft!(Fr) mul", tracked);
+    let tracked_mixed_seed = G::rand(&mut rng);
+    let tracked_mixed =
+        time(|| mixed_chain(black_box(tracked_seed), black_box(tracked_mixed_seed)));
+    report("Ft!(Fr), Ft!(Fq) mul", tracked_mixed);
+
+    let update = time(|| {
+        for i in 0..OPS {
+            update_add();
+            // keeps the counter updates from being folded into one
+            black_box(i);
+        }
+        OPS
+    });
+    report("update_add", update);
+
+    let update = time(|| {
+        for i in 0..OPS {
+            update_field::<Fr>(Op::Add, 1);
+            black_box(i);
+        }
+        OPS
+    });
+    report("update_field", update);
+
+    let update = time(|| {
+        for i in 0..OPS / 2 {
+            update_field::<Fr>(Op::Add, 1);
+            update_field::<Fq>(Op::Add, 1);
+            black_box(i);
+        }
+        OPS
+    });
+    report("update_field, 2 fields", update);
+    end_tscope!();
+
+    Tracker::reset();
+    report("overhead", tracked.saturating_sub(raw));
+    report("mixed overhead", tracked_mixed.saturating_sub(raw_mixed));
+}
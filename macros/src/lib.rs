@@ -0,0 +1,127 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::{parse_macro_input, Block, ImplItem, Item, ItemImpl, LitStr, Signature, Type};
+
+/// Instruments a function with a `field_tracker` scope.
+///
+/// The body runs inside a scope named after the function, or after the
+/// `name` argument if given: `#[tscope]` or `#[tscope(name = "sumcheck")]`.
+/// The scope is held by a guard, so it is closed on early returns and panics.
+///
+/// The body of an `async fn` is tracked with `field_tracker::future::scoped`
+/// instead, in a tracker of its own that is only installed while the future is
+/// polled. Its ops are added as a scope of the caller when it completes, so
+/// tasks interleaving on a thread or moving between threads do not mix up
+/// their scopes.
+///
+/// On an `impl` block every method gets a scope named `Type::method`, where
+/// `name` replaces `Type` if given. `const fn` methods are left as they are,
+/// since scopes cannot be opened in const contexts.
+///
+/// ```compile_fail
+/// #[field_tracker_macros::tscope]
+/// const fn degree() -> usize {
+///     2
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn tscope(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut name: Option<LitStr> = None;
+    let args_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported tscope argument, expected `name = \"...\"`"))
+        }
+    });
+    parse_macro_input!(args with args_parser);
+
+    match parse_macro_input!(item as Item) {
+        Item::Fn(item_fn) if item_fn.sig.constness.is_some() => const_fn(&item_fn.sig),
+        Item::Fn(mut item_fn) => {
+            let name = name.map_or_else(|| item_fn.sig.ident.to_string(), |name| name.value());
+            instrument_block(&name, &item_fn.sig, &mut item_fn.block);
+            item_fn.into_token_stream().into()
+        }
+        Item::Impl(item_impl) => instrument_impl(name, item_impl).into(),
+        // methods annotated directly inside an impl or trait parse as a
+        // verbatim item since they may have a receiver
+        Item::Verbatim(tokens) => match syn::parse2::<ImplItem>(tokens.clone()) {
+            Ok(ImplItem::Fn(method)) if method.sig.constness.is_some() => const_fn(&method.sig),
+            Ok(ImplItem::Fn(mut method)) => {
+                let name = name.map_or_else(|| method.sig.ident.to_string(), |name| name.value());
+                instrument_block(&name, &method.sig, &mut method.block);
+                method.into_token_stream().into()
+            }
+            _ => unsupported(tokens),
+        },
+        item => unsupported(item.into_token_stream()),
+    }
+}
+
+fn instrument_impl(name: Option<LitStr>, mut item_impl: ItemImpl) -> TokenStream2 {
+    let prefix = name.map_or_else(|| type_name(&item_impl.self_ty), |name| name.value());
+    for impl_item in item_impl.items.iter_mut() {
+        if let ImplItem::Fn(method) = impl_item {
+            if method.sig.constness.is_some() {
+                continue;
+            }
+            let name = format!("{}::{}", prefix, method.sig.ident);
+            instrument_block(&name, &method.sig, &mut method.block);
+        }
+    }
+    item_impl.into_token_stream()
+}
+
+/// Prefixes the block with a scope guard that lives until the block ends.
+///
+/// The body of an `async fn` is awaited as a scoped future instead, since a
+/// guard held across `.await` would stay open on the thread while other tasks
+/// run there.
+fn instrument_block(name: &str, sig: &Signature, block: &mut Block) {
+    let body = &block;
+    if sig.asyncness.is_some() {
+        *block = syn::parse_quote!({
+            ::field_tracker::future::scoped(#name, async move #body).await
+        });
+        return;
+    }
+    // the name is passed to tscope! as a format string
+    let name = name.replace('{', "{{").replace('}', "}}");
+    *block = syn::parse_quote!({
+        let __tscope_guard = ::field_tracker::tscope!(#name);
+        #body
+    });
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        ty => ty.to_token_stream().to_string().replace(' ', ""),
+    }
+}
+
+fn const_fn(sig: &Signature) -> TokenStream {
+    syn::Error::new_spanned(
+        sig.constness,
+        "tscope cannot be applied to a const fn, scopes cannot be opened in const contexts",
+    )
+    .to_compile_error()
+    .into()
+}
+
+fn unsupported(tokens: TokenStream2) -> TokenStream {
+    syn::Error::new_spanned(
+        tokens,
+        "tscope can only be applied to functions and impl blocks",
+    )
+    .to_compile_error()
+    .into()
+}
@@ -0,0 +1,197 @@
+// The scopes record nothing against the noop build of field-tracker.
+#![cfg(not(field_tracker_noop))]
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::task::{Context, Poll, Waker};
+
+use field_tracker::summary;
+use field_tracker::tracker::{update_add, update_mul, Op, Report};
+use field_tracker_macros::tscope;
+
+#[tscope]
+fn commit() {
+    update_add();
+}
+
+#[tscope(name = "open")]
+fn open_commitment(fail: bool) -> Result<usize, ()> {
+    update_mul();
+    if fail {
+        return Err(());
+    }
+    update_mul();
+    Ok(2)
+}
+
+struct Prover;
+
+#[tscope]
+impl Prover {
+    const fn new() -> Self {
+        Prover
+    }
+
+    fn prove(&self) {
+        update_add();
+        self.round();
+    }
+
+    fn round(&self) {
+        update_mul();
+    }
+}
+
+struct Verifier;
+
+impl Verifier {
+    #[tscope]
+    fn verify(&self) -> bool {
+        update_add();
+        true
+    }
+}
+
+#[tscope]
+async fn prove_async(value: usize) -> usize {
+    update_add();
+    std::future::ready(()).await;
+    update_mul();
+    value
+}
+
+/// Returns pending once, so that other tasks get polled in between.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[tscope]
+async fn sumcheck_round(index: usize) -> usize {
+    update_mul();
+    YieldNow(false).await;
+    update_add();
+    index
+}
+
+#[tscope]
+async fn sumcheck(rounds: usize) -> usize {
+    let mut sum = 0;
+    for index in 0..rounds {
+        sum += sumcheck_round(index).await;
+    }
+    sum
+}
+
+#[tscope]
+fn panicking() {
+    update_add();
+    panic!("prover failed");
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fn child(report: &Report, index: usize) -> &Report {
+    &report.children.as_ref().unwrap()[index]
+}
+
+#[test]
+fn test_free_functions() {
+    commit();
+    assert_eq!(open_commitment(false), Ok(2));
+    assert_eq!(open_commitment(true), Err(()));
+    update_add();
+
+    let summary = summary!();
+    assert_eq!(summary.children.as_ref().unwrap().len(), 3);
+    assert_eq!(child(&summary, 0).values[Op::Add], 1);
+    assert_eq!(child(&summary, 1).values[Op::Mul], 2);
+    assert_eq!(child(&summary, 2).values[Op::Mul], 1);
+    assert_eq!(summary.values[Op::Add], 2);
+    assert!(summary.to_string().contains("open"));
+}
+
+#[test]
+fn test_impl_blocks() {
+    const PROVER: Prover = Prover::new();
+    PROVER.prove();
+    assert!(Verifier.verify());
+
+    let summary = summary!();
+    let prove = child(&summary, 0);
+    assert!(summary.to_string().contains("Prover::prove"));
+    assert!(summary.to_string().contains("Prover::round"));
+    assert_eq!(prove.values[Op::Add], 1);
+    assert_eq!(child(prove, 0).values[Op::Mul], 1);
+    assert_eq!(child(&summary, 1).values[Op::Add], 1);
+    assert!(summary.to_string().contains("verify"));
+}
+
+#[test]
+fn test_async_fn() {
+    let future = prove_async(3);
+    // nothing runs before the future is polled
+    assert!(summary!().children.is_none());
+
+    assert_eq!(block_on(future), 3);
+    let summary = summary!();
+    assert_eq!(child(&summary, 0).values[Op::Add], 1);
+    assert_eq!(child(&summary, 0).values[Op::Mul], 1);
+}
+
+#[test]
+fn test_interleaved_async_fn() {
+    // polls both tasks in turn on this thread until they are done
+    let mut tasks = [Box::pin(sumcheck(2)), Box::pin(sumcheck(3))];
+    let mut outputs = [None, None];
+    let mut context = Context::from_waker(Waker::noop());
+    while outputs.iter().any(Option::is_none) {
+        for (task, output) in tasks.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                if let Poll::Ready(sum) = task.as_mut().poll(&mut context) {
+                    *output = Some(sum);
+                }
+            }
+        }
+    }
+    assert_eq!(outputs, [Some(1), Some(3)]);
+
+    let summary = summary!();
+    assert_eq!(summary.children.as_ref().unwrap().len(), 2);
+    for (index, rounds) in [2, 3].into_iter().enumerate() {
+        let sumcheck = child(&summary, index);
+        assert_eq!(sumcheck.name(), "sumcheck");
+        assert_eq!(sumcheck.children.as_ref().unwrap().len(), rounds);
+        assert_eq!(sumcheck.values[Op::Mul], rounds);
+        assert_eq!(child(sumcheck, 0).values[Op::Add], 1);
+    }
+}
+
+#[test]
+fn test_panic_closes_scope() {
+    assert!(std::panic::catch_unwind(panicking).is_err());
+    update_mul();
+
+    let summary = summary!();
+    assert_eq!(child(&summary, 0).values[Op::Add], 1);
+    assert_eq!(child(&summary, 0).values[Op::Mul], 0);
+    assert_eq!(summary.values[Op::Mul], 1);
+}
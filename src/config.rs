@@ -0,0 +1,163 @@
+//! Runtime configuration read from the environment.
+//!
+//! - `FIELD_TRACKER`: `off` disables tracking, `text` (the default) or `json`
+//!   selects the format used by `print_summary!`.
+//! - `FIELD_TRACKER_OUT`: file the summaries are appended to instead of
+//!   being printed to stdout.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::tracker::{Report, Tracker};
+
+pub const FIELD_TRACKER: &str = "FIELD_TRACKER";
+pub const FIELD_TRACKER_OUT: &str = "FIELD_TRACKER_OUT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// When false, no ops are recorded and `print_summary!` prints nothing.
+    pub enabled: bool,
+    pub format: OutputFormat,
+    /// Where summaries are appended, stdout when `None`.
+    pub out: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: true,
+            format: OutputFormat::Text,
+            out: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self::parse(
+            std::env::var(FIELD_TRACKER).ok().as_deref(),
+            std::env::var_os(FIELD_TRACKER_OUT).map(PathBuf::from),
+        )
+    }
+
+    /// Builds the configuration from the values of `FIELD_TRACKER` and
+    /// `FIELD_TRACKER_OUT`. Unknown modes fall back to the default.
+    pub fn parse(mode: Option<&str>, out: Option<PathBuf>) -> Self {
+        let (enabled, format) = match mode.map(|mode| mode.trim().to_ascii_lowercase()) {
+            None => (true, OutputFormat::Text),
+            Some(mode) => match mode.as_str() {
+                "off" | "0" | "false" => (false, OutputFormat::Text),
+                "" | "on" | "1" | "true" | "text" => (true, OutputFormat::Text),
+                "json" => (true, OutputFormat::Json),
+                _ => {
+                    eprintln!("field-tracker: unknown {FIELD_TRACKER} value {mode:?}, using text");
+                    (true, OutputFormat::Text)
+                }
+            },
+        };
+        Config {
+            enabled,
+            format,
+            out: out.filter(|path| !path.as_os_str().is_empty()),
+        }
+    }
+
+    /// Renders the report in the configured format.
+    pub fn format(&self, report: &Report) -> String {
+        match self.format {
+            OutputFormat::Text => report.to_string(),
+            OutputFormat::Json => report.to_json(),
+        }
+    }
+
+    /// Writes the report to the configured destination, does nothing when
+    /// tracking is disabled.
+    pub fn emit(&self, report: &Report) -> std::io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let output = self.format(report);
+        match &self.out {
+            None => {
+                println!("{}", output);
+                Ok(())
+            }
+            Some(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", output)
+            }
+        }
+    }
+}
+
+/// Configuration of the process, read from the environment on first use.
+pub fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(Config::from_env)
+}
+
+/// Emits the summary of the active tracker as configured, used by
+/// `print_summary!`.
+pub fn print_summary() {
+    if let Err(err) = config().emit(&Tracker::summary()) {
+        eprintln!("field-tracker: failed to write summary: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{Config, OutputFormat};
+    use crate::tracker::{update_add, Tracker};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Config::parse(None, None), Config::default());
+        assert!(!Config::parse(Some("off"), None).enabled);
+        assert!(!Config::parse(Some("OFF"), None).enabled);
+        assert_eq!(Config::parse(Some("text"), None).format, OutputFormat::Text);
+        assert_eq!(Config::parse(Some("json"), None).format, OutputFormat::Json);
+        assert_eq!(Config::parse(Some("yaml"), None), Config::default());
+        assert_eq!(Config::parse(None, Some(PathBuf::new())).out, None);
+        assert_eq!(
+            Config::parse(Some("json"), Some(PathBuf::from("report.json"))).out,
+            Some(PathBuf::from("report.json"))
+        );
+    }
+
+    #[test]
+    fn test_emit_to_file() {
+        let path =
+            std::env::temp_dir().join(format!("field-tracker-{}-emit.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        Tracker::start("commit");
+        update_add();
+        Tracker::end();
+        let report = Tracker::summary();
+
+        let config = Config::parse(Some("json"), Some(path.clone()));
+        config.emit(&report).unwrap();
+        config.emit(&report).unwrap();
+        Config::parse(Some("off"), Some(path.clone()))
+            .emit(&report)
+            .unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            written,
+            format!("{}\n{}\n", report.to_json(), report.to_json())
+        );
+    }
+}
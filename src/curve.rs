@@ -0,0 +1,551 @@
+//! Tracked elliptic curve groups.
+//!
+//! [`Pt`] wraps the projective points of a curve and [`PtAffine`] its affine
+//! points. They count point additions, mixed additions, doublings, scalar
+//! multiplications, normalizations and MSMs under the type of the projective
+//! group, so the ops of `G1` and `G2` are told apart in the per field
+//! breakdown. Subtractions are counted as additions and negations are free.
+//!
+//! Every MSM is also counted in a histogram of its size, with the
+//! [`Op::Custom`] kind `msm_size_2^k` for the MSMs of `2^k` up to
+//! `2^(k+1) - 1` points. Scalars and coordinates are the untracked fields of
+//! the curve, the field ops done by the point arithmetic are not counted.
+
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, ScalarMul, VariableBaseMSM};
+use ark_ff::{AdditiveGroup, PrimeField, UniformRand, Zero};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use rand::Rng;
+use std::borrow::Borrow;
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::sync::OnceLock;
+
+use crate::tracker::{update_group, Op};
+
+/// Projective point of the curve group `G` whose ops are tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Pt<G: CurveGroup> {
+    pub(crate) inner: G,
+}
+
+/// Affine point of the curve group `G` whose ops are tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PtAffine<G: CurveGroup> {
+    pub(crate) inner: G::Affine,
+}
+
+#[cfg(not(field_tracker_noop))]
+impl<G: CurveGroup> crate::Track<G> for Pt<G> {
+    fn track(value: G) -> Self {
+        from_group(value)
+    }
+
+    fn untrack(self) -> G {
+        self.inner
+    }
+}
+
+#[cfg(not(field_tracker_noop))]
+impl<G: CurveGroup> crate::Track<G::Affine> for PtAffine<G> {
+    fn track(value: G::Affine) -> Self {
+        from_affine(value)
+    }
+
+    fn untrack(self) -> G::Affine {
+        self.inner
+    }
+}
+
+const fn from_group<G: CurveGroup>(value: G) -> Pt<G> {
+    Pt { inner: value }
+}
+
+const fn from_affine<G: CurveGroup>(value: G::Affine) -> PtAffine<G> {
+    PtAffine { inner: value }
+}
+
+/// Records an MSM of `size` points, along with its histogram bucket.
+fn record_msm<G: CurveGroup>(size: usize) {
+    update_group::<G>(Op::Msm, 1);
+    update_group::<G>(msm_size_bucket(size), 1);
+}
+
+/// Histogram kind of the MSMs of `size` points. Empty MSMs are counted as
+/// `msm_size_0`.
+fn msm_size_bucket(size: usize) -> Op {
+    static BUCKETS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    if size == 0 {
+        return Op::Custom("msm_size_0");
+    }
+    let buckets = BUCKETS.get_or_init(|| {
+        (0..usize::BITS)
+            .map(|k| &*format!("msm_size_2^{k}").leak())
+            .collect()
+    });
+    Op::Custom(buckets[size.ilog2() as usize])
+}
+
+impl<G: CurveGroup> CurveGroup for Pt<G> {
+    type Config = G::Config;
+    type BaseField = G::BaseField;
+    type Affine = PtAffine<G>;
+    type FullGroup = G::FullGroup;
+
+    fn normalize_batch(v: &[Self]) -> Vec<Self::Affine> {
+        update_group::<G>(Op::Normalize, v.len());
+        let points = v.iter().map(|p| p.inner).collect::<Vec<_>>();
+        G::normalize_batch(&points)
+            .into_iter()
+            .map(from_affine)
+            .collect()
+    }
+}
+
+impl<G: CurveGroup> PrimeGroup for Pt<G> {
+    type ScalarField = G::ScalarField;
+
+    fn generator() -> Self {
+        from_group(G::generator())
+    }
+
+    fn mul_bigint(&self, other: impl AsRef<[u64]>) -> Self {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_group(self.inner.mul_bigint(other))
+    }
+
+    fn mul_bits_be(&self, other: impl Iterator<Item = bool>) -> Self {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_group(self.inner.mul_bits_be(other))
+    }
+}
+
+impl<G: CurveGroup> AdditiveGroup for Pt<G> {
+    type Scalar = G::ScalarField;
+    const ZERO: Self = from_group(G::ZERO);
+
+    fn double(&self) -> Self {
+        update_group::<G>(Op::PointDouble, 1);
+        from_group(self.inner.double())
+    }
+
+    fn double_in_place(&mut self) -> &mut Self {
+        update_group::<G>(Op::PointDouble, 1);
+        self.inner.double_in_place();
+        self
+    }
+
+    fn neg_in_place(&mut self) -> &mut Self {
+        self.inner.neg_in_place();
+        self
+    }
+}
+
+impl<G: CurveGroup> ScalarMul for Pt<G> {
+    type MulBase = PtAffine<G>;
+
+    const NEGATION_IS_CHEAP: bool = G::NEGATION_IS_CHEAP;
+
+    fn batch_convert_to_mul_base(bases: &[Self]) -> Vec<Self::MulBase> {
+        Self::normalize_batch(bases)
+    }
+
+    fn batch_mul(self, v: &[Self::ScalarField]) -> Vec<Self::MulBase> {
+        update_group::<G>(Op::ScalarMul, v.len());
+        self.inner
+            .batch_mul(v)
+            .into_iter()
+            .map(from_affine)
+            .collect()
+    }
+}
+
+impl<G: CurveGroup> VariableBaseMSM for Pt<G> {
+    fn msm_bigint(
+        bases: &[Self::MulBase],
+        bigints: &[<Self::ScalarField as PrimeField>::BigInt],
+    ) -> Self {
+        record_msm::<G>(bases.len().min(bigints.len()));
+        let bases = bases.iter().map(|b| b.inner).collect::<Vec<_>>();
+        from_group(G::msm_bigint(&bases, bigints))
+    }
+}
+
+impl<G: CurveGroup> AffineRepr for PtAffine<G> {
+    type Config = G::Config;
+    type ScalarField = G::ScalarField;
+    type BaseField = G::BaseField;
+    type Group = Pt<G>;
+
+    fn xy(&self) -> Option<(Self::BaseField, Self::BaseField)> {
+        self.inner.xy()
+    }
+
+    fn zero() -> Self {
+        from_affine(G::Affine::zero())
+    }
+
+    fn generator() -> Self {
+        from_affine(G::Affine::generator())
+    }
+
+    fn from_random_bytes(bytes: &[u8]) -> Option<Self> {
+        G::Affine::from_random_bytes(bytes).map(from_affine)
+    }
+
+    fn mul_bigint(&self, by: impl AsRef<[u64]>) -> Self::Group {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_group(self.inner.mul_bigint(by))
+    }
+
+    fn clear_cofactor(&self) -> Self {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_affine(self.inner.clear_cofactor())
+    }
+
+    fn mul_by_cofactor_to_group(&self) -> Self::Group {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_group(self.inner.mul_by_cofactor_to_group())
+    }
+}
+
+impl<G: CurveGroup> From<G> for Pt<G> {
+    fn from(value: G) -> Self {
+        from_group(value)
+    }
+}
+
+impl<G: CurveGroup> From<PtAffine<G>> for Pt<G> {
+    fn from(value: PtAffine<G>) -> Self {
+        from_group(value.inner.into())
+    }
+}
+
+impl<G: CurveGroup> From<Pt<G>> for PtAffine<G> {
+    fn from(value: Pt<G>) -> Self {
+        update_group::<G>(Op::Normalize, 1);
+        from_affine(value.inner.into_affine())
+    }
+}
+
+impl<G: CurveGroup> Zero for Pt<G> {
+    fn zero() -> Self {
+        from_group(G::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.inner.is_zero()
+    }
+}
+
+impl<G: CurveGroup> Neg for Pt<G> {
+    type Output = Pt<G>;
+
+    fn neg(self) -> Self::Output {
+        from_group(-self.inner)
+    }
+}
+
+impl<G: CurveGroup> Neg for PtAffine<G> {
+    type Output = PtAffine<G>;
+
+    fn neg(self) -> Self::Output {
+        from_affine(neg_mul_base::<G>(self.inner))
+    }
+}
+
+/// Negates an affine point, which is only known to give an affine point
+/// through the bounds of `ScalarMul`.
+fn neg_mul_base<G: ScalarMul>(point: G::MulBase) -> G::MulBase {
+    -point
+}
+
+impl<G: CurveGroup> Display for Pt<G> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl<G: CurveGroup> Display for PtAffine<G> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl<G: CurveGroup> UniformRand for Pt<G> {
+    fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        from_group(G::rand(rng))
+    }
+}
+
+impl<G: CurveGroup> UniformRand for PtAffine<G> {
+    fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        from_affine(G::Affine::rand(rng))
+    }
+}
+
+impl<G: CurveGroup> zeroize::Zeroize for Pt<G> {
+    fn zeroize(&mut self) {
+        self.inner.zeroize();
+    }
+}
+
+impl<G: CurveGroup> zeroize::Zeroize for PtAffine<G> {
+    fn zeroize(&mut self) {
+        self.inner.zeroize();
+    }
+}
+
+/// Implements the serialization traits by delegating to the untracked point.
+macro_rules! impl_serialization {
+    ($point:ident, $inner:ty, $from:ident) => {
+        impl<G: CurveGroup> CanonicalSerialize for $point<G> {
+            fn serialize_with_mode<W: Write>(
+                &self,
+                writer: W,
+                compress: Compress,
+            ) -> Result<(), SerializationError> {
+                self.inner.serialize_with_mode(writer, compress)
+            }
+
+            fn serialized_size(&self, compress: Compress) -> usize {
+                self.inner.serialized_size(compress)
+            }
+        }
+
+        impl<G: CurveGroup> CanonicalDeserialize for $point<G> {
+            fn deserialize_with_mode<R: Read>(
+                reader: R,
+                compress: Compress,
+                validate: Validate,
+            ) -> Result<Self, SerializationError> {
+                <$inner>::deserialize_with_mode(reader, compress, validate).map($from)
+            }
+        }
+
+        impl<G: CurveGroup> Valid for $point<G> {
+            fn check(&self) -> Result<(), SerializationError> {
+                self.inner.check()
+            }
+        }
+    };
+}
+
+impl_serialization!(Pt, G, from_group);
+impl_serialization!(PtAffine, G::Affine, from_affine);
+
+/// Implements an operator of projective points and its assigning form for
+/// the owned, borrowed and mutably borrowed right hand sides.
+macro_rules! impl_group_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $rhs:ty, $op:expr) => {
+        impl<G: CurveGroup> $trait<$rhs> for Pt<G> {
+            type Output = Pt<G>;
+
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                update_group::<G>($op, 1);
+                from_group(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<'a, G: CurveGroup> $trait<&'a $rhs> for Pt<G> {
+            type Output = Pt<G>;
+
+            fn $method(self, rhs: &'a $rhs) -> Self::Output {
+                update_group::<G>($op, 1);
+                from_group(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<'a, G: CurveGroup> $trait<&'a mut $rhs> for Pt<G> {
+            type Output = Pt<G>;
+
+            fn $method(self, rhs: &'a mut $rhs) -> Self::Output {
+                update_group::<G>($op, 1);
+                from_group(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<G: CurveGroup> $assign_trait<$rhs> for Pt<G> {
+            fn $assign_method(&mut self, rhs: $rhs) {
+                update_group::<G>($op, 1);
+                self.inner.$assign_method(rhs.inner)
+            }
+        }
+
+        impl<'a, G: CurveGroup> $assign_trait<&'a $rhs> for Pt<G> {
+            fn $assign_method(&mut self, rhs: &'a $rhs) {
+                update_group::<G>($op, 1);
+                self.inner.$assign_method(rhs.inner)
+            }
+        }
+
+        impl<'a, G: CurveGroup> $assign_trait<&'a mut $rhs> for Pt<G> {
+            fn $assign_method(&mut self, rhs: &'a mut $rhs) {
+                update_group::<G>($op, 1);
+                self.inner.$assign_method(rhs.inner)
+            }
+        }
+    };
+}
+
+impl_group_op!(Add, add, AddAssign, add_assign, Self, Op::PointAdd);
+impl_group_op!(Sub, sub, SubAssign, sub_assign, Self, Op::PointAdd);
+impl_group_op!(Add, add, AddAssign, add_assign, PtAffine<G>, Op::MixedAdd);
+impl_group_op!(Sub, sub, SubAssign, sub_assign, PtAffine<G>, Op::MixedAdd);
+
+impl<G: CurveGroup, S: Borrow<G::ScalarField>> Mul<S> for Pt<G> {
+    type Output = Pt<G>;
+
+    fn mul(self, rhs: S) -> Self::Output {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_group(self.inner * rhs.borrow())
+    }
+}
+
+impl<G: CurveGroup, S: Borrow<G::ScalarField>> MulAssign<S> for Pt<G> {
+    fn mul_assign(&mut self, rhs: S) {
+        update_group::<G>(Op::ScalarMul, 1);
+        self.inner *= rhs.borrow()
+    }
+}
+
+/// Implements an operator of affine points, whose result is projective, for
+/// the owned and borrowed right hand sides.
+macro_rules! impl_affine_op {
+    ($trait:ident, $method:ident, $rhs:ty, $op:expr) => {
+        impl<G: CurveGroup> $trait<$rhs> for PtAffine<G> {
+            type Output = Pt<G>;
+
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                update_group::<G>($op, 1);
+                from_group(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<'a, G: CurveGroup> $trait<&'a $rhs> for PtAffine<G> {
+            type Output = Pt<G>;
+
+            fn $method(self, rhs: &'a $rhs) -> Self::Output {
+                update_group::<G>($op, 1);
+                from_group(self.inner.$method(rhs.inner))
+            }
+        }
+    };
+}
+
+impl_affine_op!(Add, add, Self, Op::MixedAdd);
+impl_affine_op!(Sub, sub, Self, Op::MixedAdd);
+impl_affine_op!(Add, add, Pt<G>, Op::MixedAdd);
+impl_affine_op!(Sub, sub, Pt<G>, Op::MixedAdd);
+
+impl<G: CurveGroup, S: Borrow<G::ScalarField>> Mul<S> for PtAffine<G> {
+    type Output = Pt<G>;
+
+    fn mul(self, rhs: S) -> Self::Output {
+        update_group::<G>(Op::ScalarMul, 1);
+        from_group(self.inner * rhs.borrow())
+    }
+}
+
+impl<G: CurveGroup> Sum<Self> for Pt<G> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut iterator = iter;
+        let sum = iterator.next();
+        iterator
+            .fold(sum, |acc, val| acc.map(|v| v + val))
+            .unwrap_or(Self::zero())
+    }
+}
+
+impl<'a, G: CurveGroup> Sum<&'a Self> for Pt<G> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        let mut iterator = iter;
+        let sum = iterator.next().cloned();
+        iterator
+            .fold(sum, |acc, val| acc.map(|v| v + val))
+            .unwrap_or(Self::zero())
+    }
+}
+
+impl<G: CurveGroup> Sum<PtAffine<G>> for Pt<G> {
+    fn sum<I: Iterator<Item = PtAffine<G>>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, val| acc + val)
+    }
+}
+
+impl<'a, G: CurveGroup> Sum<&'a PtAffine<G>> for Pt<G> {
+    fn sum<I: Iterator<Item = &'a PtAffine<G>>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, val| acc + val)
+    }
+}
+
+#[cfg(all(test, not(field_tracker_noop)))]
+mod tests {
+    use ark_bn254::{Fr, G1Projective};
+    use ark_ec::{CurveGroup, VariableBaseMSM};
+    use ark_ff::{AdditiveGroup, UniformRand};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::msm_size_bucket;
+    use crate::tracker::{FieldType, Op, Tracker};
+    use crate::{Pt, Track};
+
+    type G1 = Pt!(G1Projective);
+
+    #[test]
+    fn test_msm_size_bucket() {
+        assert_eq!(msm_size_bucket(0), Op::Custom("msm_size_0"));
+        assert_eq!(msm_size_bucket(1), Op::Custom("msm_size_2^0"));
+        assert_eq!(msm_size_bucket(3), Op::Custom("msm_size_2^1"));
+        assert_eq!(msm_size_bucket(1024), Op::Custom("msm_size_2^10"));
+        assert_eq!(msm_size_bucket(2047), Op::Custom("msm_size_2^10"));
+    }
+
+    #[test]
+    fn test_point_ops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (a, b) = (G1Projective::rand(&mut rng), G1Projective::rand(&mut rng));
+        let scalars = [Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        let (ta, tb) = (G1::from(a), G1::from(b));
+
+        Tracker::reset();
+        Tracker::start("commit");
+        let sum = ta + tb;
+        let doubled = ta.double();
+        let mixed = ta + tb.into_affine();
+        let product = ta * scalars[0];
+        let bases = G1::normalize_batch(&[ta, tb, sum]);
+        let msm = G1::msm(&bases, &scalars).unwrap();
+        Tracker::end();
+
+        let affine = <G1 as CurveGroup>::Affine::track(a.into_affine());
+        assert_eq!(affine.untrack(), a.into_affine());
+        assert_eq!(sum.untrack(), a + b);
+        assert_eq!(doubled.untrack(), a.double());
+        assert_eq!(mixed.untrack(), a + b);
+        assert_eq!(product.untrack(), a * scalars[0]);
+        assert_eq!(
+            msm.untrack(),
+            a * scalars[0] + b * scalars[1] + (a + b) * scalars[2]
+        );
+
+        let summary = Tracker::summary();
+        let commit = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(commit.values[Op::PointAdd], 1);
+        assert_eq!(commit.values[Op::MixedAdd], 1);
+        assert_eq!(commit.values[Op::PointDouble], 1);
+        assert_eq!(commit.values[Op::ScalarMul], 1);
+        assert_eq!(commit.values[Op::Normalize], 4);
+        // the point ops inside the MSM are not counted on their own
+        assert_eq!(commit.values[Op::Msm], 1);
+        assert_eq!(commit.values[Op::Custom("msm_size_2^1")], 1);
+
+        let g1 = summary.values.field(FieldType::of_group::<G1Projective>());
+        assert_eq!(g1[Op::Msm], 1);
+        assert_eq!(g1[Op::PointAdd], 1);
+    }
+}
@@ -0,0 +1,733 @@
+//! Tracked extension fields, e.g. the `Fq2`, `Fq6` and `Fq12` towers of
+//! pairing-friendly curves.
+//!
+//! [`FtExt`] counts its ops like [`struct@crate::Ft`] does, under the type of the
+//! extension field. With [`Tracker::set_expand_extension`] enabled every op
+//! but `sqrt`, whose cost depends on the element, also records the ops of the
+//! base prime field it implies, estimated with [`ExtensionCost`]. Those are counted in the breakdown of the base prime
+//! field only, where they add up with the ops of a tracked base field, and
+//! not in the totals, which already count the extension field op.
+//!
+//! [`Tracker::set_expand_extension`]: crate::tracker::Tracker::set_expand_extension
+
+use ark_ff::{
+    AdditiveGroup, BigInt, CubicExtConfig, CubicExtField, Field, Fp, Fp12, Fp12Config, Fp2,
+    Fp6Config, FpConfig, LegendreSymbol, One, PrimeField, QuadExtConfig, QuadExtField, UniformRand,
+    Zero,
+};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize,
+    CanonicalSerializeWithFlags, Compress, Flags, Read, SerializationError, Valid, Validate, Write,
+};
+use rand::Rng;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::{Iterator, Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::tracker::{update_base_field_cost, update_field, update_field_exponentiation_cost, Op};
+use crate::{from_primefield, square_and_multiply_cost, Ft};
+
+/// Ops of the base prime field performed by an extension field op.
+/// Subtractions, negations and doublings are counted as additions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BaseCost {
+    pub add: usize,
+    pub mul: usize,
+    pub square: usize,
+    pub inv: usize,
+}
+
+impl BaseCost {
+    pub fn ops(&self) -> [(Op, usize); 4] {
+        [
+            (Op::Add, self.add),
+            (Op::Mul, self.mul),
+            (Op::Square, self.square),
+            (Op::Inv, self.inv),
+        ]
+    }
+}
+
+impl Add for BaseCost {
+    type Output = BaseCost;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        BaseCost {
+            add: self.add + rhs.add,
+            mul: self.mul + rhs.mul,
+            square: self.square + rhs.square,
+            inv: self.inv + rhs.inv,
+        }
+    }
+}
+
+impl Mul<usize> for BaseCost {
+    type Output = BaseCost;
+
+    fn mul(self, rhs: usize) -> Self::Output {
+        BaseCost {
+            add: self.add * rhs,
+            mul: self.mul * rhs,
+            square: self.square * rhs,
+            inv: self.inv * rhs,
+        }
+    }
+}
+
+/// Cost model of a field in ops of its base prime field.
+///
+/// Extensions are costed from the field they extend, so towers like
+/// `Fq12 = Fq6[w]`, `Fq6 = Fq2[v]`, `Fq2 = Fq[u]` are handled level by level.
+/// Multiplications use the Karatsuba counts (3 for quadratic, 6 for cubic
+/// extensions), squarings the complex method and Chung-Hasan SQR2.
+/// Multiplications by the non-residue are not counted. The Frobenius map
+/// multiplies coefficients by constants of the field its coefficients are
+/// given in, and the Legendre symbol is that of the norm in the base prime
+/// field, an exponentiation by `(p - 1) / 2`.
+pub trait ExtensionCost: Field {
+    fn add_cost() -> BaseCost;
+    fn mul_cost() -> BaseCost;
+    fn square_cost() -> BaseCost;
+    fn inverse_cost() -> BaseCost;
+    fn frobenius_cost() -> BaseCost;
+    fn legendre_cost() -> BaseCost;
+}
+
+/// Cost of multiplying an element of `F` by a Frobenius coefficient in its
+/// subfield `C`.
+fn frobenius_coeff_cost<F: Field, C: ExtensionCost>() -> BaseCost {
+    C::mul_cost() * (F::extension_degree() / C::extension_degree()) as usize
+}
+
+impl<P: FpConfig<N>, const N: usize> ExtensionCost for Fp<P, N> {
+    fn add_cost() -> BaseCost {
+        BaseCost {
+            add: 1,
+            ..BaseCost::default()
+        }
+    }
+
+    fn mul_cost() -> BaseCost {
+        BaseCost {
+            mul: 1,
+            ..BaseCost::default()
+        }
+    }
+
+    fn square_cost() -> BaseCost {
+        BaseCost {
+            square: 1,
+            ..BaseCost::default()
+        }
+    }
+
+    fn inverse_cost() -> BaseCost {
+        BaseCost {
+            inv: 1,
+            ..BaseCost::default()
+        }
+    }
+
+    fn frobenius_cost() -> BaseCost {
+        BaseCost::default()
+    }
+
+    fn legendre_cost() -> BaseCost {
+        let (square, mul) = square_and_multiply_cost(Self::MODULUS_MINUS_ONE_DIV_TWO.as_ref());
+        BaseCost {
+            square,
+            mul,
+            ..BaseCost::default()
+        }
+    }
+}
+
+impl<P: QuadExtConfig> ExtensionCost for QuadExtField<P>
+where
+    P::BaseField: ExtensionCost,
+    P::FrobCoeff: ExtensionCost,
+{
+    fn add_cost() -> BaseCost {
+        P::BaseField::add_cost() * 2
+    }
+
+    fn mul_cost() -> BaseCost {
+        P::BaseField::mul_cost() * 3 + P::BaseField::add_cost() * 5
+    }
+
+    fn square_cost() -> BaseCost {
+        P::BaseField::mul_cost() * 2 + P::BaseField::add_cost() * 4
+    }
+
+    fn inverse_cost() -> BaseCost {
+        P::BaseField::square_cost() * 2
+            + P::BaseField::inverse_cost()
+            + P::BaseField::mul_cost() * 2
+            + P::BaseField::add_cost() * 2
+    }
+
+    fn frobenius_cost() -> BaseCost {
+        P::BaseField::frobenius_cost() * 2 + frobenius_coeff_cost::<P::BaseField, P::FrobCoeff>()
+    }
+
+    fn legendre_cost() -> BaseCost {
+        // the norm c0^2 - nonresidue * c1^2
+        P::BaseField::square_cost() * 2 + P::BaseField::add_cost() + P::BaseField::legendre_cost()
+    }
+}
+
+impl<P: CubicExtConfig> ExtensionCost for CubicExtField<P>
+where
+    P::BaseField: ExtensionCost,
+    P::FrobCoeff: ExtensionCost,
+{
+    fn add_cost() -> BaseCost {
+        P::BaseField::add_cost() * 3
+    }
+
+    fn mul_cost() -> BaseCost {
+        P::BaseField::mul_cost() * 6 + P::BaseField::add_cost() * 15
+    }
+
+    fn square_cost() -> BaseCost {
+        P::BaseField::mul_cost() * 2
+            + P::BaseField::square_cost() * 3
+            + P::BaseField::add_cost() * 10
+    }
+
+    fn inverse_cost() -> BaseCost {
+        P::BaseField::square_cost() * 3
+            + P::BaseField::mul_cost() * 9
+            + P::BaseField::inverse_cost()
+            + P::BaseField::add_cost() * 6
+    }
+
+    fn frobenius_cost() -> BaseCost {
+        P::BaseField::frobenius_cost() * 3
+            + frobenius_coeff_cost::<P::BaseField, P::FrobCoeff>() * 2
+    }
+
+    fn legendre_cost() -> BaseCost {
+        // the norm x * x^q * x^(q^2), with q the order of the base field
+        Self::frobenius_cost() * 2 + Self::mul_cost() * 2 + P::BaseField::legendre_cost()
+    }
+}
+
+/// Tracked extension field, usually declared with the `FtExt!` macro.
+///
+/// `N` is the number of limbs of the base prime field, whose elements are
+/// tracked as `Ft<N, T::BasePrimeField>`.
+#[derive(Debug, Clone, Eq, PartialEq, Copy, Default, Ord, PartialOrd)]
+pub struct FtExt<const N: usize, T: Field> {
+    inner: T,
+}
+
+#[cfg(not(field_tracker_noop))]
+impl<const N: usize, T: Field> crate::Track<T> for FtExt<N, T> {
+    fn track(value: T) -> Self {
+        from_extension(value)
+    }
+
+    fn untrack(self) -> T {
+        self.inner
+    }
+}
+
+/// Records `op` on the extension field along with the base field cost.
+fn record<T: ExtensionCost>(op: Op, cost: impl FnOnce() -> BaseCost) {
+    update_field::<T>(op, 1);
+    update_base_field_cost::<T::BasePrimeField>(&cost().ops());
+}
+
+const fn from_extension<const N: usize, T: Field>(value: T) -> FtExt<N, T> {
+    FtExt { inner: value }
+}
+
+impl<const N: usize, P: Fp12Config> FtExt<N, Fp12<P>> {
+    /// Multiplies by a sparse element with coefficients `c0`, `c3` and `c4`,
+    /// as done by the Miller loop of D-type twists.
+    pub fn mul_by_034(
+        &mut self,
+        c0: &FtExt<N, Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>>,
+        c3: &FtExt<N, Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>>,
+        c4: &FtExt<N, Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>>,
+    ) where
+        Fp12<P>: ExtensionCost,
+        Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>: ExtensionCost,
+    {
+        record::<Fp12<P>>(Op::Custom("mul_by_034"), sparse_mul_cost::<P>);
+        self.inner.mul_by_034(&c0.inner, &c3.inner, &c4.inner);
+    }
+
+    /// Multiplies by a sparse element with coefficients `c0`, `c1` and `c4`,
+    /// as done by the Miller loop of M-type twists.
+    pub fn mul_by_014(
+        &mut self,
+        c0: &FtExt<N, Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>>,
+        c1: &FtExt<N, Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>>,
+        c4: &FtExt<N, Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>>,
+    ) where
+        Fp12<P>: ExtensionCost,
+        Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>: ExtensionCost,
+    {
+        record::<Fp12<P>>(Op::Custom("mul_by_014"), sparse_mul_cost::<P>);
+        self.inner.mul_by_014(&c0.inner, &c1.inner, &c4.inner);
+    }
+}
+
+/// Both sparse multiplications do 13 `Fp2` multiplications and 29 `Fp2`
+/// additions.
+fn sparse_mul_cost<P: Fp12Config>() -> BaseCost
+where
+    Fp2<<P::Fp6Config as Fp6Config>::Fp2Config>: ExtensionCost,
+{
+    type Fp2Of<P> = Fp2<<<P as Fp12Config>::Fp6Config as Fp6Config>::Fp2Config>;
+    Fp2Of::<P>::mul_cost() * 13 + Fp2Of::<P>::add_cost() * 29
+}
+
+impl<const N: usize, T> Field for FtExt<N, T>
+where
+    T: ExtensionCost,
+    T::BasePrimeField: PrimeField<BigInt = BigInt<N>>,
+{
+    type BasePrimeField = Ft<N, T::BasePrimeField>;
+
+    const SQRT_PRECOMP: Option<ark_ff::SqrtPrecomputation<Self>> = None;
+
+    const ONE: Self = from_extension(T::ONE);
+
+    fn extension_degree() -> u64 {
+        T::extension_degree()
+    }
+
+    fn to_base_prime_field_elements(
+        &self,
+    ) -> impl Iterator<Item = <Self as Field>::BasePrimeField> {
+        self.inner
+            .to_base_prime_field_elements()
+            .map(from_primefield)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn from_base_prime_field_elems(
+        elems: impl IntoIterator<Item = Self::BasePrimeField>,
+    ) -> Option<Self> {
+        T::from_base_prime_field_elems(elems.into_iter().map(|v| v.inner).collect::<Vec<_>>())
+            .map(|v| from_extension(v))
+    }
+
+    fn from_base_prime_field(elem: Self::BasePrimeField) -> Self {
+        from_extension(T::from_base_prime_field(elem.inner))
+    }
+
+    fn from_random_bytes_with_flags<F: Flags>(bytes: &[u8]) -> Option<(Self, F)> {
+        T::from_random_bytes_with_flags(bytes).map(|(v, f)| (from_extension(v), f))
+    }
+
+    fn legendre(&self) -> LegendreSymbol {
+        record::<T>(Op::Legendre, T::legendre_cost);
+        self.inner.legendre()
+    }
+
+    fn square(&self) -> Self {
+        record::<T>(Op::Square, T::square_cost);
+        from_extension(self.inner.square())
+    }
+
+    fn square_in_place(&mut self) -> &mut Self {
+        record::<T>(Op::Square, T::square_cost);
+        self.inner.square_in_place();
+        self
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        record::<T>(Op::Inv, T::inverse_cost);
+        self.inner.inverse().map(|v| from_extension(v))
+    }
+
+    fn inverse_in_place(&mut self) -> Option<&mut Self> {
+        record::<T>(Op::Inv, T::inverse_cost);
+        let inner = self.inner.inverse_in_place();
+        if inner.is_none() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    fn frobenius_map_in_place(&mut self, power: usize) {
+        record::<T>(Op::Frobenius, T::frobenius_cost);
+        self.inner.frobenius_map_in_place(power)
+    }
+
+    fn sqrt(&self) -> Option<Self> {
+        update_field::<T>(Op::Sqrt, 1);
+        self.inner.sqrt().map(|v| from_extension(v))
+    }
+
+    fn pow<S: AsRef<[u64]>>(&self, exp: S) -> Self {
+        let (square, mul) = square_and_multiply_cost(exp.as_ref());
+        record::<T>(Op::Pow, || T::square_cost() * square + T::mul_cost() * mul);
+        update_field_exponentiation_cost::<T>(square, mul);
+        from_extension(self.inner.pow(exp))
+    }
+
+    fn mul_by_base_prime_field(&self, elem: &Self::BasePrimeField) -> Self {
+        record::<T>(Op::Mul, || BaseCost {
+            mul: T::extension_degree() as usize,
+            ..BaseCost::default()
+        });
+        from_extension(self.inner.mul_by_base_prime_field(&elem.inner))
+    }
+}
+
+impl<const N: usize, T> AdditiveGroup for FtExt<N, T>
+where
+    T: ExtensionCost,
+    T::BasePrimeField: PrimeField<BigInt = BigInt<N>>,
+{
+    type Scalar = FtExt<N, T>;
+    const ZERO: Self = from_extension(T::ZERO);
+
+    fn double(&self) -> Self {
+        record::<T>(Op::Double, T::add_cost);
+        from_extension(self.inner.double())
+    }
+
+    fn double_in_place(&mut self) -> &mut Self {
+        record::<T>(Op::Double, T::add_cost);
+        self.inner.double_in_place();
+        self
+    }
+
+    fn neg_in_place(&mut self) -> &mut Self {
+        record::<T>(Op::Neg, T::add_cost);
+        self.inner.neg_in_place();
+        self
+    }
+}
+
+impl<const N: usize, T: Field> Display for FtExt<N, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+impl<const N: usize, T: ExtensionCost> Zero for FtExt<N, T> {
+    fn zero() -> Self {
+        from_extension(T::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.inner.is_zero()
+    }
+}
+
+impl<const N: usize, T: ExtensionCost> One for FtExt<N, T> {
+    fn one() -> Self {
+        from_extension(T::one())
+    }
+}
+
+impl<const N: usize, T: ExtensionCost> Neg for FtExt<N, T> {
+    type Output = FtExt<N, T>;
+
+    fn neg(self) -> Self::Output {
+        record::<T>(Op::Neg, T::add_cost);
+        from_extension(self.inner.neg())
+    }
+}
+
+impl<const N: usize, T: Field> UniformRand for FtExt<N, T> {
+    fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        from_extension(T::rand(rng))
+    }
+}
+
+impl<const N: usize, T: Field> zeroize::Zeroize for FtExt<N, T> {
+    fn zeroize(&mut self) {
+        self.inner.zeroize();
+    }
+}
+
+impl<const N: usize, T: Field> Hash for FtExt<N, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash(state);
+    }
+}
+
+impl<const N: usize, T: Field> CanonicalSerialize for FtExt<N, T> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.inner.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.inner.serialized_size(compress)
+    }
+}
+
+impl<const N: usize, T: Field> CanonicalSerializeWithFlags for FtExt<N, T> {
+    fn serialize_with_flags<W: Write, F: Flags>(
+        &self,
+        writer: W,
+        flags: F,
+    ) -> Result<(), SerializationError> {
+        self.inner.serialize_with_flags(writer, flags)
+    }
+
+    fn serialized_size_with_flags<F: Flags>(&self) -> usize {
+        self.inner.serialized_size_with_flags::<F>()
+    }
+}
+
+impl<const N: usize, T: Field> CanonicalDeserialize for FtExt<N, T> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        T::deserialize_with_mode(reader, compress, validate).map(|v| from_extension(v))
+    }
+}
+
+impl<const N: usize, T: Field> Valid for FtExt<N, T> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.inner.check()
+    }
+}
+
+impl<const N: usize, T: Field> CanonicalDeserializeWithFlags for FtExt<N, T> {
+    fn deserialize_with_flags<R: Read, F: Flags>(
+        reader: R,
+    ) -> Result<(Self, F), SerializationError> {
+        T::deserialize_with_flags(reader).map(|v| (from_extension(v.0), v.1))
+    }
+}
+
+/// Implements a binary operator and its assigning form for the owned,
+/// borrowed and mutably borrowed right hand sides.
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:expr, $cost:expr) => {
+        impl<const N: usize, T: ExtensionCost> $trait<Self> for FtExt<N, T> {
+            type Output = FtExt<N, T>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                record::<T>($op, $cost);
+                from_extension(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<'a, const N: usize, T: ExtensionCost> $trait<&'a Self> for FtExt<N, T> {
+            type Output = FtExt<N, T>;
+
+            fn $method(self, rhs: &'a Self) -> Self::Output {
+                record::<T>($op, $cost);
+                from_extension(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<'a, const N: usize, T: ExtensionCost> $trait<&'a mut Self> for FtExt<N, T> {
+            type Output = FtExt<N, T>;
+
+            fn $method(self, rhs: &'a mut Self) -> Self::Output {
+                record::<T>($op, $cost);
+                from_extension(self.inner.$method(rhs.inner))
+            }
+        }
+
+        impl<const N: usize, T: ExtensionCost> $assign_trait<Self> for FtExt<N, T> {
+            fn $assign_method(&mut self, rhs: Self) {
+                record::<T>($op, $cost);
+                self.inner.$assign_method(rhs.inner)
+            }
+        }
+
+        impl<'a, const N: usize, T: ExtensionCost> $assign_trait<&'a Self> for FtExt<N, T> {
+            fn $assign_method(&mut self, rhs: &'a Self) {
+                record::<T>($op, $cost);
+                self.inner.$assign_method(rhs.inner)
+            }
+        }
+
+        impl<'a, const N: usize, T: ExtensionCost> $assign_trait<&'a mut Self> for FtExt<N, T> {
+            fn $assign_method(&mut self, rhs: &'a mut Self) {
+                record::<T>($op, $cost);
+                self.inner.$assign_method(rhs.inner)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, AddAssign, add_assign, Op::Add, T::add_cost);
+impl_binary_op!(Sub, sub, SubAssign, sub_assign, Op::Sub, T::add_cost);
+impl_binary_op!(Mul, mul, MulAssign, mul_assign, Op::Mul, T::mul_cost);
+impl_binary_op!(
+    Div,
+    div,
+    DivAssign,
+    div_assign,
+    Op::Div,
+    || T::inverse_cost() + T::mul_cost()
+);
+
+impl<const N: usize, T: ExtensionCost> Sum<Self> for FtExt<N, T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut iterator = iter;
+        let sum = iterator.next();
+        iterator
+            .fold(sum, |acc, val| acc.map(|v| v + val))
+            .unwrap_or(Self::zero())
+    }
+}
+
+impl<'a, const N: usize, T: ExtensionCost> Sum<&'a Self> for FtExt<N, T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        let mut iterator = iter;
+        let sum = iterator.next().cloned();
+        iterator
+            .fold(sum, |acc, val| acc.map(|v| v + val))
+            .unwrap_or(Self::zero())
+    }
+}
+
+impl<const N: usize, T: ExtensionCost> Product<Self> for FtExt<N, T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut iterator = iter;
+        let prod = iterator.next();
+        iterator
+            .fold(prod, |acc, val| acc.map(|v| v * val))
+            .unwrap_or(Self::one())
+    }
+}
+
+impl<'a, const N: usize, T: ExtensionCost> Product<&'a Self> for FtExt<N, T> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        let mut iterator = iter;
+        let prod = iterator.next().cloned();
+        iterator
+            .fold(prod, |acc, val| acc.map(|v| v * val))
+            .unwrap_or(Self::one())
+    }
+}
+
+/// Implements the conversions from integers that `Field` requires.
+macro_rules! impl_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl<const N: usize, T: Field> From<$int> for FtExt<N, T> {
+                fn from(value: $int) -> Self {
+                    from_extension(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(u128, u64, u32, u16, u8, i128, i64, i32, i16, i8, bool);
+
+#[cfg(all(test, not(field_tracker_noop)))]
+mod tests {
+    use ark_bn254::{Fq, Fq12, Fq2, Fq6};
+    use ark_ff::{Field, UniformRand};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{BaseCost, ExtensionCost};
+    use crate::tracker::{FieldType, Op, Tracker};
+    use crate::{Ft, FtExt, Track};
+
+    type F = Ft!(Fq);
+    type F12 = FtExt!(Fq12);
+    type F2 = FtExt!(Fq2);
+
+    #[test]
+    fn test_tower_costs() {
+        assert_eq!(Fq2::mul_cost().mul, 3);
+        assert_eq!(Fq6::mul_cost().mul, 18);
+        assert_eq!(Fq12::mul_cost().mul, 54);
+        assert_eq!(Fq12::square_cost().mul, 36);
+        assert_eq!(
+            Fq12::add_cost(),
+            BaseCost {
+                add: 12,
+                ..BaseCost::default()
+            }
+        );
+        assert_eq!(Fq12::inverse_cost().inv, 1);
+        assert_eq!(Fq2::frobenius_cost().mul, 1);
+        assert_eq!(Fq6::frobenius_cost().mul, 3 + 2 * 3);
+        assert_eq!(Fq12::frobenius_cost().mul, 2 * 9 + 3 * 3);
+        assert_eq!(
+            Fq2::legendre_cost(),
+            Fq::square_cost() * 2 + Fq::add_cost() + Fq::legendre_cost()
+        );
+        assert_eq!(Fq::legendre_cost().square, 253);
+    }
+
+    #[test]
+    fn test_extension_ops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a = Fq12::rand(&mut rng);
+        let b = Fq12::rand(&mut rng);
+        let (ta, tb) = (F12::track(a), F12::track(b));
+
+        Tracker::reset();
+        Tracker::start("miller loop");
+        let product = ta * tb;
+        let inverse = tb.inverse().unwrap();
+        let quotient = ta / tb;
+        Tracker::end();
+
+        assert_eq!(product.untrack(), a * b);
+        assert_eq!(inverse.untrack(), b.inverse().unwrap());
+        assert_eq!(quotient.untrack(), a / b);
+
+        let summary = Tracker::summary();
+        let fq12 = summary.values.field(FieldType::of::<Fq12>());
+        assert_eq!(fq12[Op::Mul], 1);
+        assert_eq!(fq12[Op::Inv], 1);
+        assert_eq!(fq12[Op::Div], 1);
+        // base field costs are only recorded on request
+        assert!(summary.values.field(FieldType::of::<Fq>()).is_empty());
+
+        Tracker::reset();
+        Tracker::set_expand_extension(true);
+        let mut sparse = ta;
+        let (c0, c3, c4) = (F2::from(1u64), F2::from(2u64), F2::from(3u64));
+        sparse.mul_by_034(&c0, &c3, &c4);
+        let _ = ta * tb;
+        // base field ops are added to those of the tracked base field
+        let _ = F::from(2u64) * F::from(3u64);
+        Tracker::set_expand_extension(false);
+
+        let mut expected = a;
+        expected.mul_by_034(&c0.untrack(), &c3.untrack(), &c4.untrack());
+        assert_eq!(sparse.untrack(), expected);
+
+        let summary = Tracker::summary();
+        let fq12 = summary.values.field(FieldType::of::<Fq12>());
+        assert_eq!(fq12[Op::Custom("mul_by_034")], 1);
+        assert_eq!(fq12[Op::Mul], 1);
+        let fq = summary.values.field(FieldType::of::<Fq>());
+        assert_eq!(fq[Op::Mul], 13 * 3 + 54 + 1);
+        // the totals count each op once, the base field cost is implied
+        assert_eq!(summary.values[Op::Mul], 2);
+        assert_eq!(summary.values[Op::Custom("mul_by_034")], 1);
+
+        Tracker::reset();
+        Tracker::set_expand_extension(true);
+        let _ = ta.frobenius_map(1);
+        let _ = c3.legendre();
+        Tracker::set_expand_extension(false);
+        let fq = Tracker::summary().values.field(FieldType::of::<Fq>());
+        let expected = Fq12::frobenius_cost() + Fq2::legendre_cost();
+        assert_eq!(fq[Op::Mul], expected.mul);
+        assert_eq!(fq[Op::Square], expected.square);
+    }
+}
@@ -0,0 +1,199 @@
+//! Task-local tracking for futures.
+//!
+//! The tracker of a thread is shared by every task the thread polls, so
+//! scopes of tasks that interleave at `.await` points would corrupt each other.
+//! [`track`] gives a future its own tracker, which is installed on whatever
+//! thread polls it, only while it is polled. [`scoped`] does the same and adds
+//! what the future tracked as a scope of whichever tracker is active when it
+//! completes, which is how `#[tscope]` instruments an `async fn`.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::tracker::{Report, Tracker};
+
+/// Future returned by [`track`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Tracked<F> {
+    future: Pin<Box<F>>,
+    tracker: Option<Tracker>,
+}
+
+/// Tracks the ops of `future` in a tracker of its own, using the settings of
+/// the active tracker. The returned future outputs the result of `future`
+/// along with its report.
+pub fn track<F: Future>(future: F) -> Tracked<F> {
+    track_with(future, Tracker::new_like_active())
+}
+
+/// Like [`track`], but records into the given tracker, whose report, including
+/// anything it recorded before, is returned when the future completes.
+pub fn track_with<F: Future>(future: F, tracker: Tracker) -> Tracked<F> {
+    Tracked {
+        future: Box::pin(future),
+        tracker: Some(tracker),
+    }
+}
+
+impl<F: Future> Future for Tracked<F> {
+    type Output = (F::Output, Report);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let tracker = this
+            .tracker
+            .as_mut()
+            .expect("future polled after completion");
+        let poll = tracker.run(|| this.future.as_mut().poll(cx));
+        poll.map(|output| (output, this.tracker.take().unwrap().report()))
+    }
+}
+
+/// Future returned by [`scoped`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Scoped<F> {
+    future: Pin<Box<F>>,
+    name: Cow<'static, str>,
+    tracker: Option<Tracker>,
+}
+
+/// Tracks the ops of `future` in a tracker of its own, like [`track`], and adds
+/// them as a scope named `name` to the tracker active on the thread that
+/// completes or drops it. The tracker is created when the future is first
+/// polled, with the settings of the active tracker.
+pub fn scoped<F: Future>(name: impl Into<Cow<'static, str>>, future: F) -> Scoped<F> {
+    Scoped {
+        future: Box::pin(future),
+        name: name.into(),
+        tracker: None,
+    }
+}
+
+impl<F> Scoped<F> {
+    fn finish(&mut self) {
+        if let Some(tracker) = self.tracker.take() {
+            Tracker::add_scope(std::mem::take(&mut self.name), tracker);
+        }
+    }
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let tracker = this.tracker.get_or_insert_with(Tracker::new_like_active);
+        let poll = tracker.run(|| this.future.as_mut().poll(cx));
+        if poll.is_ready() {
+            this.finish();
+        }
+        poll
+    }
+}
+
+impl<F> Drop for Scoped<F> {
+    fn drop(&mut self) {
+        // a future dropped before completing still reports what it did
+        self.finish();
+    }
+}
+
+#[cfg(all(test, not(field_tracker_noop)))]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    use super::track;
+    use crate::tracker::{update_add, update_mul, Op, Report, Tracker};
+
+    /// Returns pending once, so that other tasks get polled in between.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    async fn prove(rounds: usize) -> usize {
+        Tracker::start("prove");
+        for _ in 0..rounds {
+            let _guard = Tracker::scope("round");
+            update_mul();
+            YieldNow(false).await;
+            update_add();
+        }
+        Tracker::end();
+        rounds
+    }
+
+    fn check(report: &Report, rounds: usize) {
+        let prove = &report.children.as_ref().unwrap()[0];
+        let children = prove.children.as_ref().unwrap();
+        assert_eq!(report.children.as_ref().unwrap().len(), 1);
+        assert_eq!(children.len(), rounds);
+        assert!(children.iter().all(|round| round.values[Op::Mul] == 1));
+        assert!(children.iter().all(|round| round.values[Op::Add] == 1));
+        assert_eq!(prove.values[Op::Mul], rounds);
+    }
+
+    #[test]
+    fn test_interleaved_tasks() {
+        // polls every task in turn until all of them are done
+        let mut tasks = [track(prove(3)), track(prove(5)), track(prove(1))];
+        let mut reports = [None, None, None];
+        let mut context = Context::from_waker(Waker::noop());
+        while reports.iter().any(Option::is_none) {
+            for (task, report) in tasks.iter_mut().zip(reports.iter_mut()) {
+                if report.is_none() {
+                    if let Poll::Ready(output) = Pin::new(&mut *task).poll(&mut context) {
+                        *report = Some(output);
+                    }
+                }
+            }
+        }
+
+        for (rounds, report) in [3, 5, 1].into_iter().zip(reports) {
+            let (output, report) = report.unwrap();
+            assert_eq!(output, rounds);
+            check(&report, rounds);
+        }
+        // the thread tracker saw none of it
+        assert!(Tracker::summary().values.is_empty());
+        assert!(Tracker::summary().children.is_none());
+    }
+
+    #[test]
+    fn test_task_moving_between_threads() {
+        let mut task = track(prove(4));
+        let mut result = None;
+        while result.is_none() {
+            let (returned, poll) = std::thread::spawn(move || {
+                let poll = Pin::new(&mut task).poll(&mut Context::from_waker(Waker::noop()));
+                // a worker thread that polled the task keeps an empty tracker
+                assert!(Tracker::summary().values.is_empty());
+                (task, poll)
+            })
+            .join()
+            .unwrap();
+            task = returned;
+            if let Poll::Ready(output) = poll {
+                result = Some(output);
+            }
+        }
+
+        let (output, report) = result.unwrap();
+        assert_eq!(output, 4);
+        check(&report, 4);
+    }
+}
@@ -1,17 +1,27 @@
-use ark_ff::{AdditiveGroup, BigInt, FftField, Field, One, PrimeField, UniformRand, Zero};
+use ark_ff::{
+    AdditiveGroup, BigInt, FftField, Field, One, PrimeField, SqrtPrecomputation, UniformRand, Zero,
+};
 use ark_serialize::{
     CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize,
     CanonicalSerializeWithFlags, Compress, Flags, Read, SerializationError, Valid, Validate, Write,
 };
 use num_bigint::BigUint;
 use rand::Rng;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::iter::{Iterator, Product, Sum};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use std::str::FromStr;
-use tracker::{update_add, update_inv, update_mul, update_sub, Report, Tracker};
-
+use tracker::{update_field, update_field_exponentiation_cost, Op, Report, Tracker};
+
+pub mod config;
+pub mod curve;
+pub mod extension;
+pub mod future;
+pub mod pairing;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod tracker;
 pub mod util;
 
@@ -21,7 +31,7 @@ pub struct Ft<const N: usize, T: PrimeField> {
 }
 
 impl<const N: usize, T: PrimeField> Ft<N, T> {
-    pub fn start(name: &'static str) {
+    pub fn start(name: impl Into<Cow<'static, str>>) {
         Tracker::start(name);
     }
 
@@ -34,6 +44,41 @@ impl<const N: usize, T: PrimeField> Ft<N, T> {
     }
 }
 
+/// Conversion between a tracked type and the arkworks type it wraps.
+///
+/// With `--cfg field_tracker_noop` `Ft!(T)`, `FtExt!(T)` and the other macros
+/// expand to `T` itself, which converts to itself, so code converting with
+/// `track` and `untrack` builds either way.
+pub trait Track<T>: Sized {
+    /// Tracks the ops of `value` from now on.
+    fn track(value: T) -> Self;
+
+    /// The untracked value.
+    fn untrack(self) -> T;
+}
+
+#[cfg(field_tracker_noop)]
+impl<T> Track<T> for T {
+    fn track(value: T) -> Self {
+        value
+    }
+
+    fn untrack(self) -> T {
+        self
+    }
+}
+
+#[cfg(not(field_tracker_noop))]
+impl<const N: usize, T: PrimeField> Track<T> for Ft<N, T> {
+    fn track(value: T) -> Self {
+        from_primefield(value)
+    }
+
+    fn untrack(self) -> T {
+        self.inner
+    }
+}
+
 impl<const N: usize, T: PrimeField<BigInt = BigInt<N>>> PrimeField for Ft<N, T> {
     type BigInt = BigInt<N>;
     const MODULUS: Self::BigInt = T::MODULUS;
@@ -126,25 +171,30 @@ impl<const N: usize, T: PrimeField<BigInt = BigInt<N>>> Field for Ft<N, T> {
     }
 
     fn legendre(&self) -> ark_ff::LegendreSymbol {
+        update_field::<T>(Op::Legendre, 1);
+        let (square, mul) = square_and_multiply_cost(T::MODULUS_MINUS_ONE_DIV_TWO.as_ref());
+        update_field_exponentiation_cost::<T>(square, mul);
         self.inner.legendre()
     }
 
     fn square(&self) -> Self {
+        update_field::<T>(Op::Square, 1);
         from_primefield(self.inner.square())
     }
 
     fn square_in_place(&mut self) -> &mut Self {
+        update_field::<T>(Op::Square, 1);
         self.inner.square_in_place();
         self
     }
 
     fn inverse(&self) -> Option<Self> {
-        update_inv();
+        update_field::<T>(Op::Inv, 1);
         self.inner.inverse().map(|v| from_primefield(v))
     }
 
     fn inverse_in_place(&mut self) -> Option<&mut Self> {
-        update_inv();
+        update_field::<T>(Op::Inv, 1);
         let inner = self.inner.inverse_in_place();
         if inner.is_none() {
             None
@@ -154,15 +204,27 @@ impl<const N: usize, T: PrimeField<BigInt = BigInt<N>>> Field for Ft<N, T> {
     }
 
     fn frobenius_map_in_place(&mut self, power: usize) {
+        // the frobenius map is the identity on a prime field, so it implies no other ops
+        update_field::<T>(Op::Frobenius, 1);
         self.inner.frobenius_map_in_place(power)
     }
 
     fn sqrt(&self) -> Option<Self> {
+        update_field::<T>(Op::Sqrt, 1);
+        let (square, mul) = sqrt_cost::<T>();
+        update_field_exponentiation_cost::<T>(square, mul);
         self.inner.sqrt().map(|v| from_primefield(v))
     }
 
+    fn pow<S: AsRef<[u64]>>(&self, exp: S) -> Self {
+        update_field::<T>(Op::Pow, 1);
+        let (square, mul) = square_and_multiply_cost(exp.as_ref());
+        update_field_exponentiation_cost::<T>(square, mul);
+        from_primefield(self.inner.pow(exp))
+    }
+
     fn mul_by_base_prime_field(&self, elem: &Self::BasePrimeField) -> Self {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         from_primefield(self.inner.mul_by_base_prime_field(&elem.inner))
     }
 }
@@ -170,6 +232,54 @@ impl<const N: usize, T: PrimeField<BigInt = BigInt<N>>> Field for Ft<N, T> {
 impl<const N: usize, T: PrimeField<BigInt = BigInt<N>>> AdditiveGroup for Ft<N, T> {
     type Scalar = Ft<N, T>;
     const ZERO: Self = from_primefield(T::ZERO);
+
+    fn double(&self) -> Self {
+        update_field::<T>(Op::Double, 1);
+        from_primefield(self.inner.double())
+    }
+
+    fn double_in_place(&mut self) -> &mut Self {
+        update_field::<T>(Op::Double, 1);
+        self.inner.double_in_place();
+        self
+    }
+
+    fn neg_in_place(&mut self) -> &mut Self {
+        update_field::<T>(Op::Neg, 1);
+        self.inner.neg_in_place();
+        self
+    }
+}
+
+/// Number of squarings and multiplications done by left-to-right
+/// square-and-multiply for the given exponent.
+fn square_and_multiply_cost(exp: &[u64]) -> (usize, usize) {
+    let bits = exp
+        .iter()
+        .rposition(|limb| *limb != 0)
+        .map_or(0, |i| i * 64 + (64 - exp[i].leading_zeros() as usize));
+    let ones = exp.iter().map(|limb| limb.count_ones() as usize).sum();
+    (bits, ones)
+}
+
+/// Estimated number of squarings and multiplications of a square root.
+///
+/// For Tonelli-Shanks this is the exponentiation by `(t - 1) / 2` plus the
+/// worst case of the main loop, which is bounded by the two-adicity `s`.
+fn sqrt_cost<T: PrimeField>() -> (usize, usize) {
+    match T::SQRT_PRECOMP {
+        Some(SqrtPrecomputation::Case3Mod4 {
+            modulus_plus_one_div_four,
+        }) => {
+            let (square, mul) = square_and_multiply_cost(modulus_plus_one_div_four);
+            (square + 1, mul)
+        }
+        _ => {
+            let s = T::TWO_ADICITY as usize;
+            let (square, mul) = square_and_multiply_cost(T::TRACE_MINUS_ONE_DIV_TWO.as_ref());
+            (square + s * (s + 1) / 2 + 1, mul + 2 * s + 2)
+        }
+    }
 }
 
 const fn from_primefield<const N: usize, T: PrimeField>(value: T) -> Ft<N, T> {
@@ -196,7 +306,7 @@ impl<const N: usize, T: PrimeField> Add<Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        update_add();
+        update_field::<T>(Op::Add, 1);
         from_primefield(self.inner.add(rhs.inner))
     }
 }
@@ -211,7 +321,7 @@ impl<const N: usize, T: PrimeField> Mul<Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         from_primefield(self.inner.mul(rhs.inner))
     }
 }
@@ -220,6 +330,7 @@ impl<const N: usize, T: PrimeField> Neg for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn neg(self) -> Self::Output {
+        update_field::<T>(Op::Neg, 1);
         from_primefield(self.inner.neg())
     }
 }
@@ -298,7 +409,7 @@ impl<const N: usize, T: PrimeField> Sub<Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        update_sub();
+        update_field::<T>(Op::Sub, 1);
         from_primefield(self.inner.sub(rhs.inner))
     }
 }
@@ -307,33 +418,35 @@ impl<const N: usize, T: PrimeField> Div<Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn div(self, rhs: Self) -> Self::Output {
+        update_field::<T>(Op::Div, 1);
         from_primefield(self.inner.div(rhs.inner))
     }
 }
 
 impl<const N: usize, T: PrimeField> AddAssign<Self> for Ft<N, T> {
     fn add_assign(&mut self, rhs: Self) {
-        update_add();
+        update_field::<T>(Op::Add, 1);
         self.inner.add_assign(rhs.inner)
     }
 }
 
 impl<const N: usize, T: PrimeField> SubAssign<Self> for Ft<N, T> {
     fn sub_assign(&mut self, rhs: Self) {
-        update_sub();
+        update_field::<T>(Op::Sub, 1);
         self.inner.sub_assign(rhs.inner)
     }
 }
 
 impl<const N: usize, T: PrimeField> MulAssign<Self> for Ft<N, T> {
     fn mul_assign(&mut self, rhs: Self) {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         self.inner.mul_assign(rhs.inner)
     }
 }
 
 impl<const N: usize, T: PrimeField> DivAssign<Self> for Ft<N, T> {
     fn div_assign(&mut self, rhs: Self) {
+        update_field::<T>(Op::Div, 1);
         self.inner.div_assign(rhs.inner)
     }
 }
@@ -342,7 +455,7 @@ impl<'a, const N: usize, T: PrimeField> Add<&'a Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn add(self, rhs: &'a Self) -> Self::Output {
-        update_add();
+        update_field::<T>(Op::Add, 1);
         from_primefield(self.inner.add(rhs.inner))
     }
 }
@@ -351,7 +464,7 @@ impl<'a, const N: usize, T: PrimeField> Sub<&'a Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn sub(self, rhs: &'a Self) -> Self::Output {
-        update_sub();
+        update_field::<T>(Op::Sub, 1);
         from_primefield(self.inner.sub(rhs.inner))
     }
 }
@@ -360,7 +473,7 @@ impl<'a, const N: usize, T: PrimeField> Mul<&'a Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn mul(self, rhs: &'a Self) -> Self::Output {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         from_primefield(self.inner.mul(rhs.inner))
     }
 }
@@ -369,33 +482,35 @@ impl<'a, const N: usize, T: PrimeField> Div<&'a Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn div(self, rhs: &'a Self) -> Self::Output {
+        update_field::<T>(Op::Div, 1);
         from_primefield(self.inner.div(rhs.inner))
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> AddAssign<&'a Self> for Ft<N, T> {
     fn add_assign(&mut self, rhs: &'a Self) {
-        update_add();
+        update_field::<T>(Op::Add, 1);
         self.inner.add_assign(rhs.inner);
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> SubAssign<&'a Self> for Ft<N, T> {
     fn sub_assign(&mut self, rhs: &'a Self) {
-        update_sub();
+        update_field::<T>(Op::Sub, 1);
         self.inner.sub_assign(rhs.inner);
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> MulAssign<&'a Self> for Ft<N, T> {
     fn mul_assign(&mut self, rhs: &'a Self) {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         self.inner.mul_assign(rhs.inner);
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> DivAssign<&'a Self> for Ft<N, T> {
     fn div_assign(&mut self, rhs: &'a Self) {
+        update_field::<T>(Op::Div, 1);
         self.inner.div_assign(rhs.inner);
     }
 }
@@ -404,7 +519,7 @@ impl<'a, const N: usize, T: PrimeField> Add<&'a mut Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn add(self, rhs: &'a mut Self) -> Self::Output {
-        update_add();
+        update_field::<T>(Op::Add, 1);
         from_primefield(self.inner.add(rhs.inner))
     }
 }
@@ -413,7 +528,7 @@ impl<'a, const N: usize, T: PrimeField> Sub<&'a mut Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn sub(self, rhs: &'a mut Self) -> Self::Output {
-        update_sub();
+        update_field::<T>(Op::Sub, 1);
         from_primefield(self.inner.sub(rhs.inner))
     }
 }
@@ -422,7 +537,7 @@ impl<'a, const N: usize, T: PrimeField> Mul<&'a mut Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn mul(self, rhs: &'a mut Self) -> Self::Output {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         from_primefield(self.inner.mul(rhs.inner))
     }
 }
@@ -431,33 +546,35 @@ impl<'a, const N: usize, T: PrimeField> Div<&'a mut Self> for Ft<N, T> {
     type Output = Ft<N, T>;
 
     fn div(self, rhs: &'a mut Self) -> Self::Output {
+        update_field::<T>(Op::Div, 1);
         from_primefield(self.inner.div(rhs.inner))
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> AddAssign<&'a mut Self> for Ft<N, T> {
     fn add_assign(&mut self, rhs: &'a mut Self) {
-        update_add();
+        update_field::<T>(Op::Add, 1);
         self.inner.add_assign(rhs.inner);
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> SubAssign<&'a mut Self> for Ft<N, T> {
     fn sub_assign(&mut self, rhs: &'a mut Self) {
-        update_sub();
+        update_field::<T>(Op::Sub, 1);
         self.inner.sub_assign(rhs.inner);
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> MulAssign<&'a mut Self> for Ft<N, T> {
     fn mul_assign(&mut self, rhs: &'a mut Self) {
-        update_mul();
+        update_field::<T>(Op::Mul, 1);
         self.inner.mul_assign(rhs.inner);
     }
 }
 
 impl<'a, const N: usize, T: PrimeField> DivAssign<&'a mut Self> for Ft<N, T> {
     fn div_assign(&mut self, rhs: &'a mut Self) {
+        update_field::<T>(Op::Div, 1);
         self.inner.div_assign(rhs.inner);
     }
 }
@@ -567,12 +684,16 @@ impl<const N: usize, T: PrimeField> From<i128> for Ft<N, T> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(field_tracker_noop)))]
 mod test {
-    use ark_bn254::Fr;
-    use ark_ff::Field;
+    use ark_bn254::{Fq, Fq12, Fq2, Fq6, Fr};
+    use ark_ff::{AdditiveGroup, FftField, Field};
 
-    use crate::{end_tscope, start_tscope, summary, Ft};
+    use crate::{
+        count, end_tscope, start_tscope, summary,
+        tracker::{update_field, FieldType, Op, Tracker},
+        tscope, Ft, FtExt,
+    };
 
     type F = Ft!(Fr);
 
@@ -591,9 +712,233 @@ mod test {
 
         num4.inverse_in_place();
 
-        assert_eq!(F::summary().values.add, 2);
-        assert_eq!(F::summary().values.mul, 1);
-        assert_eq!(F::summary().values.inv, 2);
+        assert_eq!(F::summary().values[Op::Add], 2);
+        assert_eq!(F::summary().values[Op::Mul], 1);
+        assert_eq!(F::summary().values[Op::Inv], 2);
+    }
+
+    #[test]
+    fn test_div() {
+        let mut num1 = F::from(21);
+        let num2 = F::from(7);
+
+        let num3 = num1 / num2;
+        let num4 = num1 / num2;
+        num1 /= num2;
+        num1 /= num3;
+
+        assert_eq!(num3, F::from(3));
+        assert_eq!(num4, F::from(3));
+        assert_eq!(num1, F::from(1));
+        assert_eq!(F::summary().values[Op::Div], 4);
+        assert_eq!(F::summary().values[Op::Inv], 0);
+        assert_eq!(F::summary().values[Op::Mul], 0);
+    }
+
+    #[test]
+    fn test_unary_ops() {
+        let mut num1 = F::from(3);
+        let num2 = F::from(7);
+
+        assert_eq!(num1.square(), F::from(9));
+        num1.square_in_place();
+        assert_eq!(num2.double(), F::from(14));
+        num1.double_in_place();
+        assert_eq!(-num1 + num1, F::from(0));
+        num1.neg_in_place();
+
+        assert_eq!(F::summary().values[Op::Square], 2);
+        assert_eq!(F::summary().values[Op::Double], 2);
+        assert_eq!(F::summary().values[Op::Neg], 2);
+        assert_eq!(F::summary().values[Op::Add], 1);
+        assert_eq!(F::summary().values[Op::Mul], 0);
+    }
+
+    #[test]
+    fn test_exponentiation_family() {
+        let num1 = F::from(4);
+
+        assert_eq!(num1.pow([5]), F::from(1024));
+        assert_eq!(num1.sqrt().unwrap().square(), num1);
+        assert!(num1.legendre().is_qr());
+        assert_eq!(num1.frobenius_map(1), num1);
+
+        let summary = F::summary();
+        assert_eq!(summary.values[Op::Pow], 1);
+        assert_eq!(summary.values[Op::Sqrt], 1);
+        assert_eq!(summary.values[Op::Legendre], 1);
+        assert_eq!(summary.values[Op::Frobenius], 1);
+        assert_eq!(summary.values[Op::Square], 1);
+        assert_eq!(summary.values[Op::Mul], 0);
+    }
+
+    #[test]
+    fn test_expanded_pow() {
+        Tracker::set_expand_exponentiation(true);
+        let num1 = F::from(3);
+
+        // 5 = 0b101: three squarings and two multiplications
+        assert_eq!(num1.pow([5]), F::from(243));
+        assert_eq!(F::summary().values[Op::Pow], 1);
+        assert_eq!(F::summary().values[Op::Square], 3);
+        assert_eq!(F::summary().values[Op::Mul], 2);
+
+        let _ = num1.sqrt();
+        assert_eq!(F::summary().values[Op::Sqrt], 1);
+        assert!(F::summary().values[Op::Square] > 3 + Fr::TWO_ADICITY as usize);
+        Tracker::set_expand_exponentiation(false);
+    }
+
+    #[test]
+    fn test_custom_counters() {
+        let num1 = F::from(3);
+
+        start_tscope!("commit");
+        count!("merkle_path_check");
+        count!("poseidon_permutation", 4);
+        let _ = num1 * num1;
+        end_tscope!();
+        count!("poseidon_permutation", 2);
+
+        let summary = summary!();
+        assert_eq!(summary.values[Op::Custom("poseidon_permutation")], 6);
+        assert_eq!(summary.values[Op::Custom("merkle_path_check")], 1);
+        assert_eq!(summary.values[Op::Mul], 1);
+        assert_eq!(
+            summary.children.as_ref().unwrap()[0].values[Op::Custom("poseidon_permutation")],
+            4
+        );
+        assert!(summary.to_string().contains("poseidon_permutation: 6"));
+    }
+
+    #[test]
+    fn test_per_field_breakdown() {
+        type Base = Ft!(Fq);
+        let scalar = F::from(3);
+        let base = Base::from(5);
+
+        start_tscope!("verify");
+        let _ = scalar * scalar + scalar;
+        let _ = base * base;
+        count!("hash");
+        let _ = base.inverse();
+        end_tscope!();
+
+        let summary = summary!();
+        let fr = FieldType::of::<Fr>();
+        let fq = FieldType::of::<Fq>();
+        assert_eq!(fr.bits(), 254);
+        assert_ne!(fr, fq);
+
+        let verify = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(verify.values[Op::Mul], 2);
+        assert_eq!(verify.values.field(fr)[Op::Mul], 1);
+        assert_eq!(verify.values.field(fr)[Op::Add], 1);
+        assert_eq!(verify.values.field(fq)[Op::Mul], 1);
+        assert_eq!(verify.values.field(fq)[Op::Inv], 1);
+        assert_eq!(verify.values.fields().count(), 2);
+
+        let base_only = summary.for_field(fq);
+        assert_eq!(base_only.values[Op::Mul], 1);
+        assert_eq!(base_only.values[Op::Add], 0);
+        assert_eq!(base_only.values[Op::Custom("hash")], 0);
+        assert_eq!(
+            base_only.children.as_ref().unwrap()[0].self_values[Op::Inv],
+            1
+        );
+
+        let output = format!("{}", summary);
+        assert!(output.contains(&format!("{}: mul: 1, inv: 1", fq)));
+        assert!(summary.to_json().contains("\"bits\":254"));
+    }
+
+    #[test]
+    fn test_more_fields_than_pending() {
+        fn round() {
+            update_field::<Fr>(Op::Mul, 1);
+            update_field::<Fq>(Op::Mul, 1);
+            update_field::<Fq2>(Op::Mul, 1);
+            update_field::<Fq6>(Op::Mul, 1);
+            update_field::<Fq12>(Op::Mul, 1);
+            update_field::<F>(Op::Mul, 1);
+            update_field::<Ft!(Fq)>(Op::Mul, 1);
+            update_field::<FtExt!(Fq2)>(Op::Mul, 1);
+            update_field::<FtExt!(Fq12)>(Op::Mul, 1);
+        }
+
+        Tracker::reset();
+        round();
+        start_tscope!("recursion");
+        for _ in 0..10 {
+            round();
+        }
+        end_tscope!();
+
+        let summary = summary!();
+        let recursion = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(summary.values[Op::Mul], 99);
+        assert_eq!(recursion.values[Op::Mul], 90);
+        assert_eq!(recursion.values.fields().count(), 9);
+        assert!(recursion
+            .values
+            .fields()
+            .all(|(_, values)| values[Op::Mul] == 10));
+        assert_eq!(
+            summary.self_values.field(FieldType::of::<Fq12>())[Op::Mul],
+            1
+        );
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_scope_guard_macro() {
+        let num1 = F::from(3);
+
+        {
+            let _guard = tscope!("main");
+            let _ = num1 + num1;
+            let _inner = tscope!("inner");
+            let _ = num1 * num1;
+        }
+        let _ = num1 - num1;
+
+        let summary = summary!();
+        let main = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(summary.values[Op::Sub], 1);
+        assert_eq!(main.values[Op::Sub], 0);
+        assert_eq!(main.values[Op::Add], 1);
+        assert_eq!(main.children.as_ref().unwrap()[0].values[Op::Mul], 1);
+    }
+
+    #[test]
+    fn test_formatted_scope_names() {
+        let num1 = F::from(3);
+        let name = String::from("setup");
+
+        start_tscope!(name);
+        end_tscope!();
+        for round in 0..2 {
+            start_tscope!("round {}", round);
+            let _ = num1 * num1;
+            end_tscope!();
+        }
+        {
+            let layer = 4;
+            let _guard = tscope!("layer {layer}");
+            let _ = num1 + num1;
+        }
+        start_tscope!("plain {{}}");
+        end_tscope!();
+
+        let summary = summary!();
+        let children = summary.children.as_ref().unwrap();
+        assert_eq!(children[0].name(), "setup");
+        assert_eq!(children[1].name(), "round 0");
+        assert_eq!(children[2].name(), "round 1");
+        assert_eq!(children[2].values[Op::Mul], 1);
+        assert_eq!(children[3].name(), "layer 4");
+        assert_eq!(children[3].values[Op::Add], 1);
+        assert_eq!(children[4].name(), "plain {}");
     }
 
     #[test]
@@ -605,8 +950,8 @@ mod test {
         assert_eq!(sum, F::from(19));
         assert_eq!(prod, F::from(189));
 
-        assert_eq!(F::summary().values.add, 2);
-        assert_eq!(F::summary().values.mul, 2);
+        assert_eq!(F::summary().values[Op::Add], 2);
+        assert_eq!(F::summary().values[Op::Mul], 2);
     }
 
     #[test]
@@ -630,14 +975,14 @@ mod test {
         let summary = summary!();
 
         // global assertions
-        assert_eq!(summary.values.add, 2);
-        assert_eq!(summary.values.mul, 1);
-        assert_eq!(summary.values.inv, 2);
+        assert_eq!(summary.values[Op::Add], 2);
+        assert_eq!(summary.values[Op::Mul], 1);
+        assert_eq!(summary.values[Op::Inv], 2);
 
         // main assertions
-        assert_eq!(summary.children.as_ref().unwrap()[0].values.add, 2);
-        assert_eq!(summary.children.as_ref().unwrap()[0].values.mul, 1);
-        assert_eq!(summary.children.as_ref().unwrap()[0].values.inv, 1);
+        assert_eq!(summary.children.as_ref().unwrap()[0].values[Op::Add], 2);
+        assert_eq!(summary.children.as_ref().unwrap()[0].values[Op::Mul], 1);
+        assert_eq!(summary.children.as_ref().unwrap()[0].values[Op::Inv], 1);
 
         // inner assertions
         assert_eq!(
@@ -645,8 +990,7 @@ mod test {
                 .children
                 .as_ref()
                 .unwrap()[0]
-                .values
-                .add,
+                .values[Op::Add],
             1
         );
         assert_eq!(
@@ -654,8 +998,7 @@ mod test {
                 .children
                 .as_ref()
                 .unwrap()[0]
-                .values
-                .mul,
+                .values[Op::Mul],
             1
         );
         assert_eq!(
@@ -663,9 +1006,53 @@ mod test {
                 .children
                 .as_ref()
                 .unwrap()[0]
-                .values
-                .inv,
+                .values[Op::Inv],
             0
         );
     }
 }
+
+#[cfg(all(test, field_tracker_noop))]
+mod noop_test {
+    use std::any::TypeId;
+
+    use ark_bn254::{Fq2, Fr, G1Projective};
+    use ark_ec::{CurveGroup, PrimeGroup};
+    use ark_ff::Field;
+
+    use crate::{
+        count, end_tscope, start_tscope, summary, tracker::Tracker, tscope, Ft, FtExt, Pt, Track,
+    };
+
+    type F = Ft!(Fr);
+
+    #[test]
+    fn test_conversions() {
+        let a = Fr::from(7u64);
+        assert_eq!(F::track(a).untrack(), a);
+        assert_eq!(<FtExt!(Fq2)>::track(Fq2::ONE).untrack(), Fq2::ONE);
+        type G1 = Pt!(G1Projective);
+        let g = G1Projective::generator();
+        assert_eq!(G1::from(g).untrack(), g);
+        let affine = <G1 as CurveGroup>::Affine::track(g.into_affine());
+        assert_eq!(affine.untrack(), g.into_affine());
+    }
+
+    #[test]
+    fn test_compiled_out() {
+        assert_eq!(TypeId::of::<F>(), TypeId::of::<Fr>());
+
+        Tracker::reset();
+        start_tscope!("commit");
+        let _guard = tscope!("round {}", 1);
+        let a = F::from(3) * F::from(5) + F::ONE;
+        assert_eq!(a.inverse().unwrap() * a, F::ONE);
+        count!("hashes", 2);
+        crate::tracker::update_add();
+        end_tscope!();
+
+        let summary = summary!();
+        assert!(summary.values.is_empty());
+        assert!(summary.children.is_none());
+    }
+}
@@ -0,0 +1,203 @@
+//! Tracked pairings, for measuring the cost of verifiers.
+//!
+//! [`Pe`] is a pairing engine that counts its pairings, multi pairings along
+//! with the number of pairs they multiply, multi Miller loops and final
+//! exponentiations. A pairing also counts the Miller loop and the final
+//! exponentiation it is made of, so those add up over every way of computing
+//! pairings. The ops are counted in the breakdown of the untracked engine,
+//! and its groups are the tracked points of [`crate::curve`].
+
+use ark_ec::pairing::{MillerLoopOutput, Pairing, PairingOutput};
+use ark_ec::CurveGroup;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use std::marker::PhantomData;
+
+use crate::curve::{Pt, PtAffine};
+use crate::tracker::{update_pairing, Op};
+
+/// Pairing engine `E` whose pairings are tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Pe<E: Pairing> {
+    engine: PhantomData<E>,
+}
+
+/// Point of a tracked pairing engine prepared for the Miller loop.
+#[derive(Debug, Clone, Default)]
+pub struct PtPrepared<P> {
+    inner: P,
+}
+
+impl<P> PtPrepared<P> {
+    /// The untracked prepared point.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<E: Pairing> Pairing for Pe<E> {
+    type BaseField = E::BaseField;
+    type ScalarField = E::ScalarField;
+    type G1 = Pt<E::G1>;
+    type G1Affine = PtAffine<E::G1>;
+    type G1Prepared = PtPrepared<E::G1Prepared>;
+    type G2 = Pt<E::G2>;
+    type G2Affine = PtAffine<E::G2>;
+    type G2Prepared = PtPrepared<E::G2Prepared>;
+    type TargetField = E::TargetField;
+
+    fn multi_miller_loop(
+        a: impl IntoIterator<Item = impl Into<Self::G1Prepared>>,
+        b: impl IntoIterator<Item = impl Into<Self::G2Prepared>>,
+    ) -> MillerLoopOutput<Self> {
+        update_pairing::<E>(Op::MultiMillerLoop, 1);
+        let a = a.into_iter().map(|p| p.into().inner);
+        let b = b.into_iter().map(|q| q.into().inner);
+        MillerLoopOutput(E::multi_miller_loop(a, b).0)
+    }
+
+    fn final_exponentiation(mlo: MillerLoopOutput<Self>) -> Option<PairingOutput<Self>> {
+        update_pairing::<E>(Op::FinalExponentiation, 1);
+        E::final_exponentiation(MillerLoopOutput(mlo.0)).map(|v| PairingOutput(v.0))
+    }
+
+    fn multi_pairing(
+        a: impl IntoIterator<Item = impl Into<Self::G1Prepared>>,
+        b: impl IntoIterator<Item = impl Into<Self::G2Prepared>>,
+    ) -> PairingOutput<Self> {
+        let a = a
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<Self::G1Prepared>>();
+        let b = b
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<Self::G2Prepared>>();
+        update_pairing::<E>(Op::MultiPairing, 1);
+        update_pairing::<E>(Op::MultiPairingPairs, a.len().min(b.len()));
+        Self::final_exponentiation(Self::multi_miller_loop(a, b)).unwrap()
+    }
+
+    fn pairing(
+        p: impl Into<Self::G1Prepared>,
+        q: impl Into<Self::G2Prepared>,
+    ) -> PairingOutput<Self> {
+        update_pairing::<E>(Op::Pairing, 1);
+        Self::final_exponentiation(Self::multi_miller_loop([p], [q])).unwrap()
+    }
+}
+
+impl<G: CurveGroup, P: From<G>> From<Pt<G>> for PtPrepared<P> {
+    fn from(value: Pt<G>) -> Self {
+        PtPrepared {
+            inner: P::from(value.inner),
+        }
+    }
+}
+
+impl<'a, G: CurveGroup, P: From<&'a G>> From<&'a Pt<G>> for PtPrepared<P> {
+    fn from(value: &'a Pt<G>) -> Self {
+        PtPrepared {
+            inner: P::from(&value.inner),
+        }
+    }
+}
+
+impl<G: CurveGroup, P: From<G::Affine>> From<PtAffine<G>> for PtPrepared<P> {
+    fn from(value: PtAffine<G>) -> Self {
+        PtPrepared {
+            inner: P::from(value.inner),
+        }
+    }
+}
+
+impl<'a, G: CurveGroup, P: From<&'a G::Affine>> From<&'a PtAffine<G>> for PtPrepared<P> {
+    fn from(value: &'a PtAffine<G>) -> Self {
+        PtPrepared {
+            inner: P::from(&value.inner),
+        }
+    }
+}
+
+impl<P: CanonicalSerialize> CanonicalSerialize for PtPrepared<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.inner.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.inner.serialized_size(compress)
+    }
+}
+
+impl<P: CanonicalDeserialize> CanonicalDeserialize for PtPrepared<P> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        P::deserialize_with_mode(reader, compress, validate).map(|inner| PtPrepared { inner })
+    }
+}
+
+impl<P: Valid> Valid for PtPrepared<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.inner.check()
+    }
+}
+
+#[cfg(all(test, not(field_tracker_noop)))]
+mod tests {
+    use ark_bn254::{Bn254, G1Projective, G2Projective};
+    use ark_ec::pairing::Pairing;
+    use ark_ff::{AdditiveGroup, UniformRand};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::tracker::{FieldType, Op, Tracker};
+    use crate::Pe;
+
+    type E = Pe!(Bn254);
+
+    #[test]
+    fn test_pairing_ops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (a, b) = (G1Projective::rand(&mut rng), G2Projective::rand(&mut rng));
+        let (ta, tb) = (<E as Pairing>::G1::from(a), <E as Pairing>::G2::from(b));
+
+        Tracker::reset();
+        Tracker::start("verify");
+        let single = E::pairing(ta, tb);
+        let multi = E::multi_pairing([ta, ta.double()], [tb, -tb]);
+        let loops = E::multi_miller_loop([ta], [tb]);
+        let exponentiated = E::final_exponentiation(loops).unwrap();
+        Tracker::end();
+
+        assert_eq!(single.0, Bn254::pairing(a, b).0);
+        assert_eq!(multi.0, Bn254::multi_pairing([a, a.double()], [b, -b]).0);
+        assert_eq!(exponentiated.0, single.0);
+
+        let summary = Tracker::summary();
+        let verify = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(verify.values[Op::Pairing], 1);
+        assert_eq!(verify.values[Op::MultiPairing], 1);
+        assert_eq!(verify.values[Op::MultiPairingPairs], 2);
+        assert_eq!(verify.values[Op::MultiMillerLoop], 3);
+        assert_eq!(verify.values[Op::FinalExponentiation], 3);
+        // the inputs are tracked points
+        assert_eq!(verify.values[Op::PointDouble], 1);
+
+        let bn254 = verify.values.field(FieldType::of_pairing::<Bn254>());
+        assert_eq!(bn254[Op::Pairing], 1);
+        assert_eq!(bn254[Op::MultiPairingPairs], 2);
+        assert_eq!(bn254[Op::FinalExponentiation], 3);
+        assert!(summary
+            .to_string()
+            .contains("Bn<ark_bn254::curves::Config>"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Rayon helpers that keep parallel work in the scope it was started from.
+//!
+//! Each helper captures the active scope with [`Tracker::context`] before the
+//! work is split, enters it once per rayon job and joins it once the work is
+//! done. Combined with [`Tracker::set_per_thread_scopes`] the ops also get a
+//! child scope per rayon worker thread.
+
+use rayon::iter::{MapInit, ParallelIterator};
+
+use crate::tracker::{EnteredContext, Tracker};
+
+pub trait TrackedParallelIterator: ParallelIterator {
+    /// Like [`ParallelIterator::for_each`], with the ops of `op` tracked in
+    /// the active scope.
+    fn tracked_for_each<OP>(self, op: OP)
+    where
+        OP: Fn(Self::Item) + Sync + Send,
+    {
+        let context = Tracker::context();
+        self.for_each_init(|| context.enter(), |_, item| op(item));
+        context.join();
+    }
+
+    /// Like [`ParallelIterator::map`], with the ops of `map_op` tracked in
+    /// the scope active when this is called. They are added to it once the
+    /// iterator has been driven, so it should be consumed before that scope
+    /// ends.
+    fn tracked_map<F, R>(
+        self,
+        map_op: F,
+    ) -> MapInit<
+        Self,
+        impl Fn() -> EnteredContext + Sync + Send,
+        impl Fn(&mut EnteredContext, Self::Item) -> R + Sync + Send,
+    >
+    where
+        F: Fn(Self::Item) -> R + Sync + Send,
+        R: Send,
+    {
+        let context = Tracker::context();
+        self.map_init(move || context.enter(), move |_, item| map_op(item))
+    }
+}
+
+impl<I: ParallelIterator> TrackedParallelIterator for I {}
+
+/// Like [`rayon::join`], with the ops of both closures tracked in the active
+/// scope.
+pub fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    let context = Tracker::context();
+    let result = rayon::join(|| context.run(oper_a), || context.run(oper_b));
+    context.join();
+    result
+}
+
+#[cfg(all(test, not(field_tracker_noop)))]
+mod tests {
+    use rayon::prelude::*;
+    use rayon::ThreadPoolBuilder;
+
+    use super::{join, TrackedParallelIterator};
+    use crate::tracker::{update_add, update_mul, Op, Tracker};
+
+    #[test]
+    fn test_tracked_for_each() {
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        pool.install(|| {
+            Tracker::start("commit");
+            (0..100).into_par_iter().tracked_for_each(|i| {
+                update_add();
+                if i % 2 == 0 {
+                    update_mul();
+                }
+            });
+            Tracker::end();
+
+            let summary = Tracker::summary();
+            let commit = &summary.children.as_ref().unwrap()[0];
+            assert_eq!(commit.values[Op::Add], 100);
+            assert_eq!(commit.self_values[Op::Mul], 50);
+            assert_eq!(summary.self_values[Op::Add], 0);
+        });
+    }
+
+    #[test]
+    fn test_tracked_map_and_join() {
+        Tracker::start("open");
+        let evals = (0..64u64)
+            .into_par_iter()
+            .tracked_map(|i| {
+                let _guard = Tracker::scope("eval");
+                update_mul();
+                i * 2
+            })
+            .collect::<Vec<_>>();
+        let (a, b) = join(
+            || {
+                update_add();
+                1
+            },
+            || {
+                update_add();
+                2
+            },
+        );
+        Tracker::end();
+
+        assert_eq!(evals[63], 126);
+        assert_eq!(a + b, 3);
+        let summary = Tracker::summary();
+        let open = &summary.children.as_ref().unwrap()[0];
+        let eval = &open.children.as_ref().unwrap()[0];
+        assert_eq!(open.values[Op::Add], 2);
+        assert_eq!(open.values[Op::Mul], 64);
+        assert_eq!(eval.calls, 64);
+        assert_eq!(eval.values[Op::Mul], 64);
+    }
+
+    #[test]
+    fn test_per_thread_scopes() {
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        pool.install(|| {
+            Tracker::set_per_thread_scopes(true);
+            Tracker::start("commit");
+            (0..1000).into_par_iter().tracked_for_each(|_| update_mul());
+            Tracker::end();
+            Tracker::set_per_thread_scopes(false);
+
+            let summary = Tracker::summary();
+            let commit = &summary.children.as_ref().unwrap()[0];
+            let threads = commit.children.as_ref().unwrap();
+            assert!(!threads.is_empty() && threads.len() <= 4);
+            assert!(threads.iter().all(|t| t.name().starts_with("thread ")));
+            let total = threads.iter().map(|t| t.values[Op::Mul]).sum::<usize>();
+            assert_eq!(total, 1000);
+            assert_eq!(commit.values[Op::Mul], 1000);
+            assert_eq!(commit.self_values[Op::Mul], 0);
+        });
+    }
+}
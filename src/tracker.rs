@@ -1,55 +1,516 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
 use num_format::{Locale, ToFormattedString};
-use std::{cell::RefCell, fmt::Display};
+use std::{
+    any::type_name,
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt::Display,
+    ops::Index,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 use treeline::Tree;
 
+use crate::config::config;
+
 const GLOBAL_SUMMARY: &str = "Global Summary";
+const DELTA: &str = "Delta";
+const OTHER: &str = "other";
 
 thread_local! {
     static GLOBAL_TRACKER: RefCell<Tracker> = RefCell::new(Tracker::new());
+    static PENDING: PendingOps = const {
+        PendingOps {
+            recording: Cell::new(true),
+            expand_exponentiation: Cell::new(false),
+            expand_extension: Cell::new(false),
+            unattributed: [const { Cell::new(0) }; BUILTIN_OPS.len()],
+            fields: [const { PendingField::new() }; PENDING_FIELDS],
+        }
+    };
+}
+
+/// Counts of the built-in op kinds recorded since the active tracker was last
+/// accessed. Keeps the hot path of [`update`] to a few plain loads and stores,
+/// the counts are added to the active scope before anything reads or changes
+/// the tracker.
+struct PendingOps {
+    /// Whether the active tracker records ops, i.e. it is not paused and
+    /// tracking is enabled.
+    recording: Cell<bool>,
+    /// Whether the active tracker records the squarings and multiplications
+    /// of exponentiations.
+    expand_exponentiation: Cell<bool>,
+    /// Whether the active tracker records the base field cost of extension
+    /// field ops.
+    expand_extension: Cell<bool>,
+    /// Counts of the ops not attributed to a field.
+    unattributed: [Cell<usize>; BUILTIN_OPS.len()],
+    /// Counts per field, in a slot picked from the address of its name.
+    fields: [PendingField; PENDING_FIELDS],
+}
+
+/// Number of fields whose counts can be pending at once. Recording the ops of
+/// one more field adds the pending counts to the tracker first.
+const PENDING_FIELDS: usize = 8;
+
+/// Pending counts of `field`, the slot is free when it is `None`.
+struct PendingField {
+    field: Cell<Option<FieldType>>,
+    counts: [Cell<usize>; BUILTIN_OPS.len()],
+}
+
+impl PendingField {
+    const fn new() -> Self {
+        PendingField {
+            field: Cell::new(None),
+            counts: [const { Cell::new(0) }; BUILTIN_OPS.len()],
+        }
+    }
+}
+
+impl PendingOps {
+    /// Counters of the ops attributed to `field`.
+    #[inline]
+    fn counts(&self, field: Option<FieldType>) -> &[Cell<usize>; BUILTIN_OPS.len()] {
+        let Some(field) = field else {
+            return &self.unattributed;
+        };
+        // every field has a home slot, so interleaved fields do not evict
+        // each other
+        let home = &self.fields[field.home_slot()];
+        if home.field.get().is_some_and(|taken| taken.is_same(&field)) {
+            return &home.counts;
+        }
+        self.find_field(field)
+    }
+
+    /// Looks up the slot of `field` when it is not in its home slot, taking
+    /// a free one if it has none, the home slot first.
+    #[inline(never)]
+    fn find_field(&self, field: FieldType) -> &[Cell<usize>; BUILTIN_OPS.len()] {
+        let home = field.home_slot();
+        let probe = (0..PENDING_FIELDS).map(|i| (home + i) % PENDING_FIELDS);
+        let taken = |i: usize| self.fields[i].field.get();
+        let found = probe
+            .clone()
+            .find(|&i| taken(i).is_some_and(|taken| taken.is_same(&field)))
+            .or_else(|| probe.clone().find(|&i| taken(i).is_none()));
+        // with every slot taken, adding the counts to the tracker frees them
+        let index = found.unwrap_or_else(|| with_active(|_| home));
+        let slot = &self.fields[index];
+        if slot.field.get().is_none() {
+            slot.field.set(Some(field));
+        }
+        &slot.counts
+    }
+
+    fn flush(&self, tracker: &mut Tracker) {
+        // `recording` is only synced on the first access to the tracker, the
+        // counts buffered before are dropped if it does not record
+        let recording = tracker.is_recording();
+        let report = tracker.stack.last_mut().unwrap();
+        let mut record = |field, counts: &[Cell<usize>; BUILTIN_OPS.len()]| {
+            for (op, count) in BUILTIN_OPS.iter().zip(counts) {
+                let count = count.replace(0);
+                if recording && count != 0 {
+                    report.record(field, *op, count);
+                }
+            }
+        };
+        record(None, &self.unattributed);
+        for slot in &self.fields {
+            if let Some(field) = slot.field.take() {
+                record(Some(field), &slot.counts);
+            }
+        }
+    }
+
+    fn sync(&self, tracker: &Tracker) {
+        self.recording.set(tracker.is_recording());
+        self.expand_exponentiation
+            .set(tracker.expand_exponentiation);
+        self.expand_extension.set(tracker.expand_extension);
+    }
+}
+
+/// Runs `f` on the active tracker of the thread, with the pending counts
+/// added to it first.
+fn with_active<R>(f: impl FnOnce(&mut Tracker) -> R) -> R {
+    GLOBAL_TRACKER.with(|v| {
+        let tracker = &mut *v.borrow_mut();
+        PENDING.with(|pending| pending.flush(tracker));
+        let result = f(tracker);
+        PENDING.with(|pending| pending.sync(tracker));
+        result
+    })
+}
+
+/// Like [`with_active`], but does nothing if the tracker is already borrowed
+/// or destroyed. Used by drop implementations, which must not panic.
+fn try_with_active(f: impl FnOnce(&mut Tracker)) {
+    let _ = GLOBAL_TRACKER.try_with(|v| {
+        if let Ok(mut tracker) = v.try_borrow_mut() {
+            let _ = PENDING.try_with(|pending| pending.flush(&mut tracker));
+            f(&mut tracker);
+            let _ = PENDING.try_with(|pending| pending.sync(&tracker));
+        }
+    });
+}
+
+/// Declares [`Op`] along with the list of its built-in kinds, so that their
+/// names and indices in [`PendingOps`] are derived from a single list.
+macro_rules! builtin_ops {
+    (
+        $(#[$attr:meta])*
+        pub enum Op {
+            $($(#[$variant_attr:meta])* $variant:ident => $name:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        pub enum Op {
+            $($(#[$variant_attr])* $variant,)*
+            Custom(&'static str),
+        }
+
+        /// Indices of the built-in kinds in [`BUILTIN_OPS`].
+        enum BuiltinIndex {
+            $($variant,)*
+            Count,
+        }
+
+        /// Built-in op kinds in the order of their index in [`PendingOps`].
+        const BUILTIN_OPS: [Op; BuiltinIndex::Count as usize] = [$(Op::$variant,)*];
+
+        impl Op {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Op::$variant => $name,)*
+                    Op::Custom(name) => name,
+                }
+            }
+
+            /// Position of a built-in kind in [`BUILTIN_OPS`].
+            #[inline]
+            fn builtin_index(&self) -> Option<usize> {
+                match self {
+                    $(Op::$variant => Some(BuiltinIndex::$variant as usize),)*
+                    Op::Custom(_) => None,
+                }
+            }
+        }
+    };
+}
+
+builtin_ops! {
+    /// Kind of operation counted by the tracker.
+    ///
+    /// The built-in kinds are recorded by [`struct@crate::Ft`], from [`Op::PointAdd`]
+    /// on by the curve points of [`crate::curve`] and from [`Op::Pairing`] on by
+    /// the pairings of [`crate::pairing`]. [`Op::Custom`] can be used for any other
+    /// counter. Reports order kinds as they are declared here, with
+    /// custom kinds last in alphabetical order.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum Op {
+        Add => "add",
+        Sub => "sub",
+        Mul => "mul",
+        Inv => "inv",
+        Div => "div",
+        Square => "square",
+        Double => "double",
+        Neg => "neg",
+        Pow => "pow",
+        Sqrt => "sqrt",
+        Legendre => "legendre",
+        Frobenius => "frobenius",
+        /// Addition of two projective points.
+        PointAdd => "point_add",
+        /// Addition of a projective and an affine point.
+        MixedAdd => "mixed_add",
+        PointDouble => "point_double",
+        ScalarMul => "scalar_mul",
+        /// Conversion of a projective point to affine coordinates.
+        Normalize => "normalize",
+        /// Multi-scalar multiplication, of any size.
+        Msm => "msm",
+        Pairing => "pairing",
+        MultiPairing => "multi_pairing",
+        /// Number of pairs multiplied by the multi pairings.
+        MultiPairingPairs => "multi_pairing_pairs",
+        MultiMillerLoop => "multi_miller_loop",
+        FinalExponentiation => "final_exponentiation",
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
+/// Field or curve group whose ops are counted separately, identified by its
+/// type name and the bit size of its (base) prime field modulus.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldType {
+    name: &'static str,
+    bits: u32,
+}
+
+impl FieldType {
+    pub fn of<F: Field>() -> Self {
+        FieldType {
+            name: type_name::<F>(),
+            bits: F::BasePrimeField::MODULUS_BIT_SIZE,
+        }
+    }
+
+    /// The group of a curve, whose bit size is that of its base field.
+    pub fn of_group<G: CurveGroup>() -> Self {
+        FieldType {
+            name: type_name::<G>(),
+            bits: <G::BaseField as Field>::BasePrimeField::MODULUS_BIT_SIZE,
+        }
+    }
+
+    /// The pairing engine `E`, whose bit size is that of its base field.
+    pub fn of_pairing<E: Pairing>() -> Self {
+        FieldType {
+            name: type_name::<E>(),
+            bits: E::BaseField::MODULUS_BIT_SIZE,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Whether both come from the same type name, which is much cheaper to
+    /// compare on the hot path than the names. Type names of the same type
+    /// usually share their address, when they do not the field is only
+    /// counted in two places that get merged when added to the tracker.
+    #[inline]
+    fn is_same(&self, other: &Self) -> bool {
+        std::ptr::eq(self.name, other.name)
+    }
+
+    /// Slot of the pending counts the field is looked up in first.
+    #[inline]
+    fn home_slot(&self) -> usize {
+        // Fibonacci hashing, the low bits of the address vary too little,
+        // the high bits of the product mix all of them
+        let hash = (self.name.as_ptr() as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        (hash >> 32) as usize % PENDING_FIELDS
+    }
+}
+
+impl PartialEq for FieldType {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits && (self.is_same(other) || self.name == other.name)
+    }
+}
+
+impl Eq for FieldType {}
+
+impl PartialOrd for FieldType {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FieldType {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.cmp(other.name).then(self.bits.cmp(&other.bits))
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} bits)", self.name, self.bits)
+    }
+}
+
+/// Operation counts keyed by [`Op`], kinds that were never recorded read as 0.
+///
+/// Ops recorded by a tracked field are also counted per [`FieldType`].
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct ReportValues {
-    pub add: usize,
-    pub sub: usize,
-    pub mul: usize,
-    pub inv: usize,
+    counts: BTreeMap<Op, usize>,
+    fields: BTreeMap<FieldType, ReportValues>,
+}
+
+impl ReportValues {
+    pub fn get(&self, op: Op) -> usize {
+        self.counts.get(&op).copied().unwrap_or(0)
+    }
+
+    pub fn increment(&mut self, op: Op, count: usize) {
+        if count != 0 {
+            *self.counts.entry(op).or_insert(0) += count;
+        }
+    }
+
+    /// Like `increment`, also counting the ops for `field` when given.
+    pub fn increment_field(&mut self, field: Option<FieldType>, op: Op, count: usize) {
+        self.increment(op, count);
+        if let Some(field) = field {
+            if count != 0 {
+                self.fields.entry(field).or_default().increment(op, count);
+            }
+        }
+    }
+
+    /// Counts the ops for `field` only, leaving the totals as they are.
+    fn increment_breakdown(&mut self, field: FieldType, op: Op, count: usize) {
+        if count != 0 {
+            self.fields.entry(field).or_default().increment(op, count);
+        }
+    }
+
+    /// Counts of the ops recorded by `field`.
+    pub fn field(&self, field: FieldType) -> ReportValues {
+        self.fields.get(&field).cloned().unwrap_or_default()
+    }
+
+    /// Iterates over the fields that recorded ops and their counts.
+    pub fn fields(&self) -> impl Iterator<Item = (FieldType, &ReportValues)> + '_ {
+        self.fields.iter().map(|(field, values)| (*field, values))
+    }
+
+    /// Whether the counts are not all from a single field, in which case the
+    /// per field counts tell more than the totals.
+    fn is_mixed(&self) -> bool {
+        match self.fields.len() {
+            0 => false,
+            1 => self.fields.values().next().unwrap().counts != self.counts,
+            _ => true,
+        }
+    }
+
+    /// Iterates over the recorded op kinds and their counts in report order.
+    pub fn iter(&self) -> impl Iterator<Item = (Op, usize)> + '_ {
+        self.counts.iter().map(|(op, count)| (*op, *count))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn merge(&mut self, other: &ReportValues) {
+        for (op, count) in other.iter() {
+            self.increment(op, count);
+        }
+        for (field, values) in other.fields() {
+            self.fields.entry(field).or_default().merge(values);
+        }
+    }
+
+    /// Count of each op kind minus its count in `other`, floored at 0.
+    pub fn saturating_sub(&self, other: &ReportValues) -> Self {
+        let mut values = ReportValues::default();
+        for (op, count) in self.iter() {
+            values.increment(op, count.saturating_sub(other.get(op)));
+        }
+        for (field, field_values) in self.fields() {
+            let field_values = field_values.saturating_sub(&other.field(field));
+            if !field_values.is_empty() {
+                values.fields.insert(field, field_values);
+            }
+        }
+        values
+    }
+
+    /// Returns a copy where every division is replaced by the inversion and
+    /// multiplication it is implemented with.
+    pub fn with_div_expanded(&self) -> Self {
+        let mut values = self.clone();
+        if let Some(div) = values.counts.remove(&Op::Div) {
+            values.increment(Op::Mul, div);
+            values.increment(Op::Inv, div);
+        }
+        for field_values in values.fields.values_mut() {
+            *field_values = field_values.with_div_expanded();
+        }
+        values
+    }
+}
+
+impl Index<Op> for ReportValues {
+    type Output = usize;
+
+    fn index(&self, op: Op) -> &Self::Output {
+        self.counts.get(&op).unwrap_or(&0)
+    }
 }
 
 impl Display for ReportValues {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "add: {}, sub: {}, mul: {}, inv: {}",
-            self.add.to_formatted_string(&Locale::en),
-            self.sub.to_formatted_string(&Locale::en),
-            self.mul.to_formatted_string(&Locale::en),
-            self.inv.to_formatted_string(&Locale::en)
-        )
+        if self.is_empty() {
+            return f.write_str("no ops");
+        }
+        let entries = self
+            .iter()
+            .map(|(op, count)| format!("{}: {}", op, count.to_formatted_string(&Locale::en)))
+            .collect::<Vec<_>>();
+        f.write_str(entries.join(", ").as_str())
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Report {
-    name: &'static str,
+    name: Cow<'static, str>,
+    /// Ops of the scope including the ones of its children.
     pub values: ReportValues,
+    /// Ops done directly in the scope, excluding its children.
+    pub self_values: ReportValues,
     pub children: Option<Vec<Report>>,
+    /// Number of times the scope ran, more than 1 only for aggregated scopes.
+    pub calls: usize,
+    // per call extremes, only stored once the scope aggregates several calls
+    min: Option<ReportValues>,
+    max: Option<ReportValues>,
+    // number of nodes in the tree rooted at this report, itself included
+    size: usize,
 }
 
 impl Report {
-    fn new(name: &'static str) -> Self {
+    fn new(name: impl Into<Cow<'static, str>>) -> Self {
         Report {
-            name,
+            name: name.into(),
             values: ReportValues::default(),
+            self_values: ReportValues::default(),
             children: None,
+            calls: 1,
+            min: None,
+            max: None,
+            size: 1,
         }
     }
 
+    fn record(&mut self, field: Option<FieldType>, op: Op, count: usize) {
+        self.values.increment_field(field, op, count);
+        self.self_values.increment_field(field, op, count);
+    }
+
+    /// Records ops implied by others that were already counted, e.g. the base
+    /// field cost of an extension field op, in the breakdown of `field` only.
+    fn record_breakdown(&mut self, field: FieldType, op: Op, count: usize) {
+        self.values.increment_breakdown(field, op, count);
+        self.self_values.increment_breakdown(field, op, count);
+    }
+
     fn merge(&mut self, child_report: Report) {
-        self.values.add += child_report.values.add;
-        self.values.sub += child_report.values.sub;
-        self.values.mul += child_report.values.mul;
-        self.values.inv += child_report.values.inv;
+        self.values.merge(&child_report.values);
+        self.size += child_report.size;
         match &mut self.children {
             Some(children) => {
                 children.push(child_report);
@@ -58,6 +519,195 @@ impl Report {
         }
     }
 
+    /// Like `merge`, but folds the child into an existing sibling of the
+    /// same name instead of adding a new node.
+    fn merge_aggregated(&mut self, child_report: Report) {
+        self.values.merge(&child_report.values);
+        self.push_aggregated(child_report);
+    }
+
+    fn push_aggregated(&mut self, child_report: Report) {
+        let children = self.children.get_or_insert_with(Vec::new);
+        match children
+            .iter_mut()
+            .find(|child| child.name == child_report.name)
+        {
+            Some(sibling) => {
+                let size = sibling.size;
+                sibling.absorb(child_report);
+                self.size += sibling.size - size;
+            }
+            None => {
+                self.size += child_report.size;
+                children.push(child_report);
+            }
+        }
+    }
+
+    /// Folds a closed child into the tree without adding nodes: into a
+    /// sibling of the same name, or else into the `other` sibling. Only the
+    /// scopes below it that the sibling already has are kept, the ops of the
+    /// others count as the sibling's own.
+    fn merge_bounded(&mut self, mut child_report: Report) {
+        let sibling = self
+            .children
+            .iter()
+            .flatten()
+            .find(|child| child.name == child_report.name);
+        match sibling {
+            Some(sibling) if sibling.covers(&child_report) => {}
+            Some(_) => child_report.collapse(),
+            None => {
+                child_report.collapse();
+                child_report.name = Cow::Borrowed(OTHER);
+            }
+        }
+        self.merge_aggregated(child_report);
+    }
+
+    /// Whether every scope below `other` has a counterpart below this one.
+    fn covers(&self, other: &Report) -> bool {
+        other.children.iter().flatten().all(|child| {
+            self.children
+                .iter()
+                .flatten()
+                .any(|own| own.name == child.name && own.covers(child))
+        })
+    }
+
+    /// Drops the children, their ops become the scope's own.
+    fn collapse(&mut self) {
+        self.children = None;
+        self.self_values = self.values.clone();
+        self.size = 1;
+    }
+
+    /// Combines the calls of another scope with the same name into this one.
+    fn absorb(&mut self, other: Report) {
+        let min = min_values(self.min(), other.min());
+        let max = max_values(self.max(), other.max());
+        self.min = Some(min);
+        self.max = Some(max);
+        self.calls += other.calls;
+        self.values.merge(&other.values);
+        self.self_values.merge(&other.self_values);
+        for child in other.children.into_iter().flatten() {
+            self.push_aggregated(child);
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Copy of the report, and of its children, that only counts the ops
+    /// recorded by `field`.
+    pub fn for_field(&self, field: FieldType) -> Report {
+        Report {
+            name: self.name.clone(),
+            values: self.values.field(field),
+            self_values: self.self_values.field(field),
+            children: self
+                .children
+                .as_ref()
+                .map(|children| children.iter().map(|c| c.for_field(field)).collect()),
+            calls: self.calls,
+            min: self.min.as_ref().map(|min| min.field(field)),
+            max: self.max.as_ref().map(|max| max.field(field)),
+            size: self.size,
+        }
+    }
+
+    fn sort_children(&mut self) {
+        if let Some(children) = &mut self.children {
+            children.sort_by(|a, b| a.name.cmp(&b.name));
+            children.iter_mut().for_each(Report::sort_children);
+        }
+    }
+
+    /// Smallest count of each op kind over the calls of the scope.
+    pub fn min(&self) -> &ReportValues {
+        self.min.as_ref().unwrap_or(&self.values)
+    }
+
+    /// Largest count of each op kind over the calls of the scope.
+    pub fn max(&self) -> &ReportValues {
+        self.max.as_ref().unwrap_or(&self.values)
+    }
+
+    /// Average count of `op` per call of the scope.
+    pub fn mean(&self, op: Op) -> f64 {
+        self.values[op] as f64 / self.calls as f64
+    }
+
+    /// Returns a copy of the report tree where divisions are shown as the
+    /// inversion and multiplication they imply.
+    pub fn with_div_expanded(&self) -> Report {
+        Report {
+            name: self.name.clone(),
+            values: self.values.with_div_expanded(),
+            self_values: self.self_values.with_div_expanded(),
+            children: self
+                .children
+                .as_ref()
+                .map(|children| children.iter().map(|c| c.with_div_expanded()).collect()),
+            calls: self.calls,
+            min: self.min.as_ref().map(|min| min.with_div_expanded()),
+            max: self.max.as_ref().map(|max| max.with_div_expanded()),
+            size: self.size,
+        }
+    }
+
+    /// Exports the report tree as a JSON object of the form
+    /// `{"name": .., "values": {"<op>": count, ..}, "children": [..]}`.
+    ///
+    /// Each node also carries its exclusive counts as `self_values`, and
+    /// aggregated scopes carry `calls` and the per call `min` and `max`.
+    pub fn to_json(&self) -> String {
+        let children = self
+            .children
+            .iter()
+            .flatten()
+            .map(|child| child.to_json())
+            .collect::<Vec<_>>();
+        let stats = if self.calls > 1 {
+            format!(
+                ",\"calls\":{},\"min\":{},\"max\":{}",
+                self.calls,
+                json_values(self.min()),
+                json_values(self.max())
+            )
+        } else {
+            String::new()
+        };
+        let fields = if self.values.fields.is_empty() {
+            String::new()
+        } else {
+            let fields = self
+                .values
+                .fields()
+                .map(|(field, values)| {
+                    format!(
+                        "{{\"field\":{},\"bits\":{},\"values\":{}}}",
+                        json_string(field.name()),
+                        field.bits(),
+                        json_values(values)
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!(",\"fields\":[{}]", fields.join(","))
+        };
+        format!(
+            "{{\"name\":{},\"values\":{}{},\"self_values\":{}{},\"children\":[{}]}}",
+            json_string(&self.name),
+            json_values(&self.values),
+            fields,
+            json_values(&self.self_values),
+            stats,
+            children.join(",")
+        )
+    }
+
     #[allow(dead_code)]
     fn to_string(&self, tab_count: usize) -> String {
         let mut output = String::new();
@@ -83,6 +733,28 @@ impl Report {
         let mut res = Tree::root(self.name.to_string());
 
         res.push(Tree::root(self.values.to_string()));
+        if self.values.is_mixed() {
+            for (field, values) in self.values.fields() {
+                res.push(Tree::root(format!("{}: {}", field, values)));
+            }
+        }
+        if self.children.is_some() {
+            res.push(Tree::root(format!("self: {}", self.self_values)));
+        }
+        if self.calls > 1 {
+            let mean = self
+                .values
+                .iter()
+                .map(|(op, _)| format!("{}: {:.2}", op, self.mean(op)))
+                .collect::<Vec<_>>();
+            res.push(Tree::root(format!(
+                "calls: {}, min: [{}], mean: [{}], max: [{}]",
+                self.calls.to_formatted_string(&Locale::en),
+                self.min(),
+                mean.join(", "),
+                self.max()
+            )));
+        }
 
         match &self.children {
             None => {}
@@ -97,52 +769,577 @@ impl Report {
     }
 }
 
+/// Records ops into a tree of scopes.
+///
+/// Every thread has an active tracker that `Ft` ops and the associated
+/// functions like [`Tracker::start`] and [`Tracker::summary`] act on. Separate
+/// trackers can be created with [`Tracker::new`] and made the active one for
+/// a region of code with [`Tracker::run`] or [`Tracker::enter`], e.g. to keep
+/// one tracker per prover instance or test case.
 #[derive(Debug)]
 pub struct Tracker {
+    id: u64,
     stack: Vec<Report>,
+    // serial of each scope on the stack, tells apart scopes that were started
+    // at the same depth
+    serials: Vec<u64>,
+    next_serial: u64,
+    expand_exponentiation: bool,
+    expand_extension: bool,
+    aggregate: bool,
+    per_thread_scopes: bool,
+    max_nodes: Option<usize>,
+    // stack depth of the first scope started after reaching `max_nodes`
+    limited_from: Option<usize>,
+    // nesting depth of pause calls, nothing is recorded while it is not 0
+    paused: usize,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Tracker {
-    fn new() -> Self {
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
         Tracker {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             stack: vec![Report::new(GLOBAL_SUMMARY)],
+            serials: vec![0],
+            next_serial: 1,
+            expand_exponentiation: false,
+            expand_extension: false,
+            aggregate: false,
+            per_thread_scopes: false,
+            max_nodes: None,
+            limited_from: None,
+            paused: 0,
         }
     }
 
-    pub fn start(name: &'static str) {
-        GLOBAL_TRACKER.with(|v| v.borrow_mut().stack.push(Report::new(name)));
+    /// Stops recording ops on the active tracker until [`Tracker::resume`] is
+    /// called. Scopes can still be started and ended. Calls can be nested.
+    pub fn pause() {
+        with_active(|tracker| tracker.paused += 1);
+    }
+
+    pub fn resume() {
+        with_active(|tracker| {
+            if tracker.paused == 0 {
+                panic!("Tracking not Paused");
+            }
+            tracker.paused -= 1;
+        });
+    }
+
+    /// When enabled, work run through a [`TrackerContext`] is tracked in a
+    /// child scope per thread, which shows how the work was balanced.
+    pub fn set_per_thread_scopes(per_thread_scopes: bool) {
+        with_active(|tracker| tracker.per_thread_scopes = per_thread_scopes);
+    }
+
+    /// When enabled, a closed scope is folded into an earlier sibling with the
+    /// same name, which then reports the number of calls along with the total,
+    /// min, max and mean of each op kind. Keeps loop heavy reports small.
+    pub fn set_aggregate(aggregate: bool) {
+        with_active(|tracker| tracker.aggregate = aggregate);
+    }
+
+    /// Limits the number of scopes kept in the report of the active tracker,
+    /// e.g. for long running provers. Scopes started once the limit is
+    /// reached are merged when they end into an earlier sibling with the same
+    /// name, or into a sibling named `other`, so only repeated paths stay
+    /// detailed. The report can still outgrow the limit by the scopes that
+    /// were open when it was reached and one `other` node per scope. `None`
+    /// removes the limit.
+    pub fn set_max_nodes(max_nodes: Option<usize>) {
+        with_active(|tracker| tracker.max_nodes = max_nodes);
+    }
+
+    /// When enabled, exponentiations (pow, sqrt, legendre) also record the
+    /// squarings and multiplications they imply on top of their own counter.
+    pub fn set_expand_exponentiation(expand: bool) {
+        with_active(|tracker| tracker.expand_exponentiation = expand);
+    }
+
+    /// When enabled, ops of tracked extension fields also record the ops of
+    /// the base prime field they imply, see [`crate::extension`].
+    pub fn set_expand_extension(expand: bool) {
+        with_active(|tracker| tracker.expand_extension = expand);
+    }
+
+    pub fn start(name: impl Into<Cow<'static, str>>) {
+        with_active(|tracker| tracker.push(name));
     }
 
     pub fn end() {
-        GLOBAL_TRACKER.with(|v| {
-            let stack = &mut v.borrow_mut().stack;
-            if stack.len() <= 1 {
+        with_active(|tracker| {
+            if tracker.stack.len() <= 1 {
                 panic!("Tracking not Started");
             }
-            let current_active = stack.pop().unwrap();
-            stack.last_mut().unwrap().merge(current_active)
+            tracker.end_top();
         });
     }
 
+    /// Starts a scope that is ended when the returned guard is dropped,
+    /// including on early return and while unwinding from a panic.
+    pub fn scope(name: impl Into<Cow<'static, str>>) -> ScopeGuard {
+        with_active(|tracker| {
+            tracker.push(name);
+            ScopeGuard {
+                tracker_id: tracker.id,
+                depth: tracker.stack.len(),
+            }
+        })
+    }
+
+    /// Runs `f` in a new scope and returns its result along with the report of
+    /// that scope. The scope is also merged into its parent as usual.
+    pub fn measure<R>(name: impl Into<Cow<'static, str>>, f: impl FnOnce() -> R) -> (R, Report) {
+        let guard = Self::scope(name);
+        let result = f();
+        let report = with_active(|tracker| {
+            tracker.end_to(guard.depth);
+            tracker.stack.last().unwrap().clone()
+        });
+        drop(guard);
+        (result, report)
+    }
+
+    /// Ends every scope above `depth`, never ending the global scope.
+    fn end_to(&mut self, depth: usize) {
+        while self.stack.len() > depth.max(1) {
+            self.end_top();
+        }
+    }
+
+    fn push(&mut self, name: impl Into<Cow<'static, str>>) {
+        if self.limited_from.is_none() {
+            let nodes = self.stack.iter().map(|report| report.size).sum::<usize>();
+            if self.max_nodes.is_some_and(|max_nodes| nodes >= max_nodes) {
+                self.limited_from = Some(self.stack.len());
+            }
+        }
+        self.stack.push(Report::new(name));
+        self.serials.push(self.next_serial);
+        self.next_serial += 1;
+    }
+
+    fn end_top(&mut self) {
+        let current_active = self.stack.pop().unwrap();
+        self.serials.pop();
+        let depth = self.stack.len();
+        let parent = self.stack.last_mut().unwrap();
+        // scopes started once the node limit was reached are folded by name
+        // while open, then merged without new nodes when the first one ends
+        if self.limited_from == Some(depth) {
+            self.limited_from = None;
+            parent.merge_bounded(current_active)
+        } else if self.aggregate || self.limited_from.is_some() {
+            parent.merge_aggregated(current_active)
+        } else {
+            parent.merge(current_active)
+        }
+    }
+
     pub fn summary() -> Report {
-        GLOBAL_TRACKER.with(|tracker| {
-            let mut stack_copy = tracker.borrow().stack.clone();
+        with_active(|tracker| tracker.current_report())
+    }
 
-            while stack_copy.len() >= 2 {
-                let child = stack_copy
-                    .pop()
-                    .expect("confirmed stack copy has at least 2 elements");
+    /// Report of the tracker as if all open scopes were ended now.
+    fn current_report(&self) -> Report {
+        let mut stack_copy = self.stack.clone();
+
+        while stack_copy.len() >= 2 {
+            let child = stack_copy
+                .pop()
+                .expect("confirmed stack copy has at least 2 elements");
+            if self.aggregate {
+                stack_copy.last_mut().unwrap().merge_aggregated(child);
+            } else {
                 stack_copy.last_mut().unwrap().merge(child);
             }
+        }
+
+        stack_copy.pop().unwrap()
+    }
 
-            stack_copy.pop().unwrap()
+    /// Takes the current totals of the active tracker, to later get the ops
+    /// performed since then with [`Snapshot::delta`].
+    pub fn snapshot() -> Snapshot {
+        with_active(|tracker| Snapshot {
+            tracker_id: tracker.id,
+            values: tracker.total(),
         })
     }
 
-    #[allow(dead_code)]
-    fn reset() {
-        GLOBAL_TRACKER.with(|v| v.replace(Tracker::new()));
+    /// Totals over all scopes, open ones included.
+    fn total(&self) -> ReportValues {
+        let mut total = ReportValues::default();
+        for report in &self.stack {
+            total.merge(&report.values);
+        }
+        total
+    }
+
+    /// Captures the active scope so that ops done on other threads can be
+    /// attributed to it, see [`TrackerContext`].
+    pub fn context() -> TrackerContext {
+        with_active(|tracker| TrackerContext {
+            tracker_id: tracker.id,
+            serials: tracker.serials.clone(),
+            expand_exponentiation: tracker.expand_exponentiation,
+            expand_extension: tracker.expand_extension,
+            per_thread_scopes: tracker.per_thread_scopes,
+            paused: tracker.paused > 0,
+            collected: Arc::new(Mutex::new(Report::new(GLOBAL_SUMMARY))),
+        })
+    }
+
+    /// Clears the recorded ops and scopes of the active tracker, keeping its
+    /// settings.
+    pub fn reset() {
+        with_active(|tracker| *tracker = tracker.with_same_settings());
+    }
+
+    /// An empty tracker with the settings of the active tracker of the thread.
+    pub(crate) fn new_like_active() -> Tracker {
+        with_active(|tracker| tracker.with_same_settings())
+    }
+
+    /// Adds the ops of a tracker that ran elsewhere, e.g. for a task, to the
+    /// active scope as a child scope named `name`.
+    pub(crate) fn add_scope(name: Cow<'static, str>, tracker: Tracker) {
+        let mut report = tracker.current_report();
+        report.name = name;
+        try_with_active(|active| {
+            active.push(report.name.clone());
+            *active.stack.last_mut().unwrap() = report;
+            active.end_top();
+        });
+    }
+
+    fn is_recording(&self) -> bool {
+        self.paused == 0 && config().enabled
+    }
+
+    fn with_same_settings(&self) -> Tracker {
+        Tracker {
+            expand_exponentiation: self.expand_exponentiation,
+            expand_extension: self.expand_extension,
+            aggregate: self.aggregate,
+            per_thread_scopes: self.per_thread_scopes,
+            max_nodes: self.max_nodes,
+            ..Tracker::new()
+        }
+    }
+
+    /// Runs `f` with this tracker as the active tracker of the thread.
+    pub fn run<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let _entered = self.enter();
+        f()
+    }
+
+    /// Makes this tracker the active tracker of the thread until the returned
+    /// guard is dropped, then restores the previously active one.
+    pub fn enter(&mut self) -> Entered<'_> {
+        with_active(|active| std::mem::swap(self, active));
+        Entered { tracker: self }
+    }
+
+    /// Report of this tracker, with its open scopes reported as if they
+    /// ended now.
+    pub fn report(&self) -> Report {
+        self.current_report()
     }
+
+    /// Clears the recorded ops and scopes of this tracker, keeping its settings.
+    pub fn clear(&mut self) {
+        *self = Tracker {
+            id: self.id,
+            next_serial: self.next_serial,
+            ..self.with_same_settings()
+        };
+    }
+}
+
+/// Guard returned by [`Tracker::enter`].
+#[must_use = "the tracker is only active until the guard is dropped"]
+#[derive(Debug)]
+pub struct Entered<'a> {
+    tracker: &'a mut Tracker,
+}
+
+impl Drop for Entered<'_> {
+    fn drop(&mut self) {
+        // the thread tracker holds the entered one, swap the previous one back
+        try_with_active(|active| std::mem::swap(self.tracker, active));
+    }
+}
+
+/// Totals of the active tracker at the time of [`Tracker::snapshot`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    tracker_id: u64,
+    values: ReportValues,
+}
+
+impl Snapshot {
+    /// Ops performed by the active tracker since the snapshot was taken, in
+    /// any scope. If the active tracker is not the one the snapshot was taken
+    /// of, e.g. because it was reset since, all of its ops are reported.
+    pub fn delta(&self) -> Report {
+        let mut delta = Report::new(DELTA);
+        delta.values = with_active(|tracker| {
+            if tracker.id == self.tracker_id {
+                tracker.total().saturating_sub(&self.values)
+            } else {
+                tracker.total()
+            }
+        });
+        delta.self_values = delta.values.clone();
+        delta
+    }
+}
+
+/// Handle to the scope that was active when [`Tracker::context`] was called.
+///
+/// Work running on any thread, e.g. inside a parallel iterator, is tracked
+/// by wrapping it in [`TrackerContext::run`], or for a batch of work in
+/// [`TrackerContext::enter`]. Its ops are collected and added
+/// to the captured scope when the context is joined or dropped on the thread
+/// that created it, which should happen before that scope ends, otherwise they
+/// go to its closest open parent. Scopes started by the work become children
+/// of the captured scope, folded by name so the result does not depend on
+/// thread scheduling.
+#[derive(Debug)]
+pub struct TrackerContext {
+    tracker_id: u64,
+    // serials of the captured scope and its parents
+    serials: Vec<u64>,
+    expand_exponentiation: bool,
+    expand_extension: bool,
+    per_thread_scopes: bool,
+    paused: bool,
+    collected: Arc<Mutex<Report>>,
+}
+
+impl TrackerContext {
+    /// Runs `f` with its own tracker installed on the current thread and
+    /// collects its ops into the context.
+    pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _entered = self.enter();
+        f()
+    }
+
+    /// Installs a tracker of the context on the current thread until the
+    /// returned guard is dropped, which collects its ops into the context.
+    ///
+    /// Installing a tracker and collecting its ops costs far more than an op,
+    /// so parallel work should enter the context once per batch of items
+    /// rather than once per item, e.g. with rayon's `for_each_init`.
+    pub fn enter(&self) -> EnteredContext {
+        let mut worker = Tracker {
+            expand_exponentiation: self.expand_exponentiation,
+            expand_extension: self.expand_extension,
+            paused: self.paused as usize,
+            ..Tracker::new()
+        };
+        if self.per_thread_scopes {
+            worker.push(thread_label());
+        }
+        EnteredContext {
+            previous: Some(with_active(|tracker| std::mem::replace(tracker, worker))),
+            collected: Arc::clone(&self.collected),
+        }
+    }
+
+    /// Adds the collected ops to the captured scope. Equivalent to dropping
+    /// the context.
+    pub fn join(self) {}
+
+    fn merge_collected(&mut self) {
+        let mut collected = std::mem::replace(
+            &mut *self.collected.lock().unwrap_or_else(|e| e.into_inner()),
+            Report::new(GLOBAL_SUMMARY),
+        );
+        collected.sort_children();
+        try_with_active(|tracker| {
+            let index = if tracker.id == self.tracker_id {
+                // the captured scope if still open, else its closest open parent
+                let open = self.serials.iter().zip(&tracker.serials);
+                open.take_while(|(captured, open)| captured == open).count() - 1
+            } else {
+                tracker.stack.len() - 1
+            };
+            let scope = &mut tracker.stack[index];
+            scope.values.merge(&collected.values);
+            scope.self_values.merge(&collected.self_values);
+            for child in collected.children.into_iter().flatten() {
+                scope.push_aggregated(child);
+            }
+        });
+    }
+}
+
+impl Drop for TrackerContext {
+    fn drop(&mut self) {
+        self.merge_collected();
+    }
+}
+
+fn thread_label() -> String {
+    #[cfg(feature = "rayon")]
+    if let Some(index) = rayon::current_thread_index() {
+        return format!("thread {}", index);
+    }
+    let thread = std::thread::current();
+    match thread.name() {
+        Some(name) => format!("thread {}", name),
+        None => format!("thread {:?}", thread.id()),
+    }
+}
+
+/// Guard returned by [`TrackerContext::enter`]. Restores the tracker of the
+/// thread when dropped, also when the work panics, and collects what was
+/// tracked in between.
+#[must_use = "the context is only entered until the guard is dropped"]
+#[derive(Debug)]
+pub struct EnteredContext {
+    previous: Option<Tracker>,
+    collected: Arc<Mutex<Report>>,
+}
+
+impl Drop for EnteredContext {
+    fn drop(&mut self) {
+        let previous = self.previous.take().unwrap();
+        let worker = with_active(|tracker| std::mem::replace(tracker, previous));
+        let report = worker.current_report();
+        let mut collected = self.collected.lock().unwrap_or_else(|e| e.into_inner());
+        collected.values.merge(&report.values);
+        collected.self_values.merge(&report.self_values);
+        for child in report.children.into_iter().flatten() {
+            collected.push_aggregated(child);
+        }
+    }
+}
+
+/// Runs `f` without recording any of its ops, e.g. to exclude the setup of a
+/// test from its report.
+pub fn untracked<R>(f: impl FnOnce() -> R) -> R {
+    /// Resumes tracking when dropped, also on panic.
+    struct Paused;
+
+    impl Drop for Paused {
+        fn drop(&mut self) {
+            try_with_active(|tracker| tracker.paused = tracker.paused.saturating_sub(1));
+        }
+    }
+
+    Tracker::pause();
+    let _paused = Paused;
+    f()
+}
+
+/// Turns formatting arguments into a scope name, borrowing it when there is
+/// nothing to format. Used by the scope macros.
+pub fn scope_name(args: std::fmt::Arguments<'_>) -> Cow<'static, str> {
+    match args.as_str() {
+        Some(name) => Cow::Borrowed(name),
+        None => Cow::Owned(args.to_string()),
+    }
+}
+
+/// Guard returned by [`Tracker::scope`] and the `tscope!` macro.
+///
+/// Dropping it ends its scope along with any scope started inside it that was
+/// left open, so the stack stays balanced when a panic skips the inner ends.
+///
+/// The guard only acts on the tracker that created it: if it is dropped on
+/// another thread, e.g. by a future that moved between threads, it does nothing.
+#[must_use = "the scope ends as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ScopeGuard {
+    tracker_id: u64,
+    depth: usize,
+}
+
+impl ScopeGuard {
+    /// Guard that does nothing when dropped, returned by `tscope!` when
+    /// tracking is compiled out.
+    pub fn noop() -> Self {
+        ScopeGuard {
+            tracker_id: u64::MAX,
+            depth: 0,
+        }
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        if self.depth == 0 {
+            return;
+        }
+        // never panic here, this can run while unwinding or during thread teardown
+        try_with_active(|tracker| {
+            if tracker.id == self.tracker_id {
+                tracker.end_to(self.depth - 1);
+            }
+        });
+    }
+}
+
+fn min_values(a: &ReportValues, b: &ReportValues) -> ReportValues {
+    combine_values(a, b, usize::min)
+}
+
+fn max_values(a: &ReportValues, b: &ReportValues) -> ReportValues {
+    combine_values(a, b, usize::max)
+}
+
+fn combine_values(
+    a: &ReportValues,
+    b: &ReportValues,
+    combine: fn(usize, usize) -> usize,
+) -> ReportValues {
+    let mut values = ReportValues::default();
+    for (op, _) in a.iter().chain(b.iter()) {
+        if values.get(op) == 0 {
+            values.increment(op, combine(a.get(op), b.get(op)));
+        }
+    }
+    for (field, _) in a.fields().chain(b.fields()) {
+        let field_values = combine_values(&a.field(field), &b.field(field), combine);
+        if !field_values.is_empty() {
+            values.fields.insert(field, field_values);
+        }
+    }
+    values
+}
+
+fn json_values(values: &ReportValues) -> String {
+    let entries = values
+        .iter()
+        .map(|(op, count)| format!("{}:{}", json_string(op.name()), count))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", entries.join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c if (c as u32) < 0x20 => output.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
 }
 
 impl Display for Report {
@@ -151,23 +1348,150 @@ impl Display for Report {
     }
 }
 
-pub fn update_add() {
-    GLOBAL_TRACKER.with(|v| v.borrow_mut().stack.last_mut().unwrap().values.add += 1);
+/// Adds `count` to the `op` counter of the active scope.
+#[inline]
+pub fn update(op: Op, count: usize) {
+    update_in(None, op, count);
+}
+
+/// Like [`update`], also counting the ops in the breakdown of the field `F`.
+#[inline]
+pub fn update_field<F: Field>(op: Op, count: usize) {
+    update_in(Some(FieldType::of::<F>()), op, count);
+}
+
+/// Like [`update`], also counting the ops in the breakdown of the curve
+/// group `G`.
+#[inline]
+pub fn update_group<G: CurveGroup>(op: Op, count: usize) {
+    update_in(Some(FieldType::of_group::<G>()), op, count);
+}
+
+/// Like [`update`], also counting the ops in the breakdown of the pairing
+/// engine `E`.
+#[inline]
+pub fn update_pairing<E: Pairing>(op: Op, count: usize) {
+    update_in(Some(FieldType::of_pairing::<E>()), op, count);
 }
 
+#[inline]
+fn update_in(field: Option<FieldType>, op: Op, count: usize) {
+    if cfg!(field_tracker_noop) {
+        return;
+    }
+    match op.builtin_index() {
+        Some(index) => PENDING.with(|pending| {
+            if pending.recording.get() {
+                let counter = &pending.counts(field)[index];
+                counter.set(counter.get() + count);
+            }
+        }),
+        None => with_active(|tracker| {
+            if tracker.is_recording() {
+                tracker.stack.last_mut().unwrap().record(field, op, count)
+            }
+        }),
+    }
+}
+
+#[inline]
+pub fn update_add() {
+    update(Op::Add, 1);
+}
+#[inline]
 pub fn update_sub() {
-    GLOBAL_TRACKER.with(|v| v.borrow_mut().stack.last_mut().unwrap().values.sub += 1);
+    update(Op::Sub, 1);
 }
+#[inline]
 pub fn update_mul() {
-    GLOBAL_TRACKER.with(|v| v.borrow_mut().stack.last_mut().unwrap().values.mul += 1);
+    update(Op::Mul, 1);
 }
+#[inline]
 pub fn update_inv() {
-    GLOBAL_TRACKER.with(|v| v.borrow_mut().stack.last_mut().unwrap().values.inv += 1);
+    update(Op::Inv, 1);
+}
+#[inline]
+pub fn update_div() {
+    update(Op::Div, 1);
+}
+#[inline]
+pub fn update_square() {
+    update(Op::Square, 1);
+}
+#[inline]
+pub fn update_double() {
+    update(Op::Double, 1);
+}
+#[inline]
+pub fn update_neg() {
+    update(Op::Neg, 1);
+}
+#[inline]
+pub fn update_pow() {
+    update(Op::Pow, 1);
+}
+#[inline]
+pub fn update_sqrt() {
+    update(Op::Sqrt, 1);
+}
+#[inline]
+pub fn update_legendre() {
+    update(Op::Legendre, 1);
+}
+#[inline]
+pub fn update_frobenius() {
+    update(Op::Frobenius, 1);
+}
+
+/// Records the squarings and multiplications performed by an exponentiation,
+/// only if expansion is enabled with [`Tracker::set_expand_exponentiation`].
+#[inline]
+pub fn update_exponentiation_cost(square: usize, mul: usize) {
+    exponentiation_cost_in(None, square, mul);
+}
+
+/// Like [`update_exponentiation_cost`], also counting the ops in the
+/// breakdown of the field `F`.
+#[inline]
+pub fn update_field_exponentiation_cost<F: Field>(square: usize, mul: usize) {
+    exponentiation_cost_in(Some(FieldType::of::<F>()), square, mul);
+}
+
+/// Records the ops of the base prime field `F` implied by an extension field
+/// op, only if expansion is enabled with [`Tracker::set_expand_extension`].
+/// They are counted in the breakdown of `F` only, the totals already count
+/// the extension field op.
+#[inline]
+pub fn update_base_field_cost<F: Field>(cost: &[(Op, usize)]) {
+    if cfg!(field_tracker_noop) || !PENDING.with(|pending| pending.expand_extension.get()) {
+        return;
+    }
+    with_active(|tracker| {
+        if tracker.expand_extension && tracker.is_recording() {
+            let report = tracker.stack.last_mut().unwrap();
+            for (op, count) in cost {
+                report.record_breakdown(FieldType::of::<F>(), *op, *count);
+            }
+        }
+    });
+}
+
+#[inline]
+fn exponentiation_cost_in(field: Option<FieldType>, square: usize, mul: usize) {
+    if cfg!(field_tracker_noop) || !PENDING.with(|pending| pending.expand_exponentiation.get()) {
+        return;
+    }
+    update_in(field, Op::Square, square);
+    update_in(field, Op::Mul, mul);
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(field_tracker_noop)))]
 mod tests {
-    use super::{update_add, update_inv, update_mul, Tracker};
+    use super::{
+        untracked, update, update_add, update_div, update_exponentiation_cost, update_inv,
+        update_mul, update_pow, update_sub, Op, Report, Tracker, BUILTIN_OPS,
+    };
+    use crate::config::{config, FIELD_TRACKER};
 
     fn gkr_sumcheck_squence() {
         Tracker::start("gkr");
@@ -201,7 +1525,7 @@ mod tests {
         Tracker::start("GKR");
         update_inv();
         assert_eq!(
-            Tracker::summary().values.inv,
+            Tracker::summary().values[Op::Inv],
             1,
             "Wrong summary for inverse"
         );
@@ -209,27 +1533,59 @@ mod tests {
         Tracker::start("Sumcheck");
         update_add();
         update_add();
-        assert_eq!(Tracker::summary().values.add, 2, "Wrong summary for add");
+        assert_eq!(
+            Tracker::summary().values[Op::Add],
+            2,
+            "Wrong summary for add"
+        );
         update_mul();
         update_inv();
-        assert_eq!(Tracker::summary().values.add, 2, "Wrong summary for add");
-        assert_eq!(Tracker::summary().values.inv, 2, "Wrong summary for inv");
-        assert_eq!(Tracker::summary().values.mul, 1, "Wrong summary for mul");
+        assert_eq!(
+            Tracker::summary().values[Op::Add],
+            2,
+            "Wrong summary for add"
+        );
+        assert_eq!(
+            Tracker::summary().values[Op::Inv],
+            2,
+            "Wrong summary for inv"
+        );
+        assert_eq!(
+            Tracker::summary().values[Op::Mul],
+            1,
+            "Wrong summary for mul"
+        );
         Tracker::end();
 
         Tracker::start("Sumcheck");
         update_mul();
         update_mul();
         update_mul();
-        assert_eq!(Tracker::summary().values.mul, 4, "Wrong summary for mul");
+        assert_eq!(
+            Tracker::summary().values[Op::Mul],
+            4,
+            "Wrong summary for mul"
+        );
         Tracker::end();
         Tracker::end();
 
         Tracker::reset();
         Tracker::summary();
-        assert_eq!(Tracker::summary().values.add, 0, "Wrong summary for add");
-        assert_eq!(Tracker::summary().values.inv, 0, "Wrong summary for inv");
-        assert_eq!(Tracker::summary().values.mul, 0, "Wrong summary for mul");
+        assert_eq!(
+            Tracker::summary().values[Op::Add],
+            0,
+            "Wrong summary for add"
+        );
+        assert_eq!(
+            Tracker::summary().values[Op::Inv],
+            0,
+            "Wrong summary for inv"
+        );
+        assert_eq!(
+            Tracker::summary().values[Op::Mul],
+            0,
+            "Wrong summary for mul"
+        );
     }
 
     #[test]
@@ -258,9 +1614,21 @@ mod tests {
         update_inv();
         update_mul();
         Tracker::end();
-        assert_eq!(Tracker::summary().values.add, 2, "Wrong number of add op");
-        assert_eq!(Tracker::summary().values.mul, 1, "Wrong number of mul op");
-        assert_eq!(Tracker::summary().values.inv, 1, "Wrong number of inv op");
+        assert_eq!(
+            Tracker::summary().values[Op::Add],
+            2,
+            "Wrong number of add op"
+        );
+        assert_eq!(
+            Tracker::summary().values[Op::Mul],
+            1,
+            "Wrong number of mul op"
+        );
+        assert_eq!(
+            Tracker::summary().values[Op::Inv],
+            1,
+            "Wrong number of inv op"
+        );
         Tracker::reset();
     }
 
@@ -274,16 +1642,13 @@ mod tests {
 
     #[test]
     fn test_correct_summary_report() {
-        // TODO: figure out a better way to test summary history change
-        //  new way should be robust to new tracking items e.g div, exp ...
-
         Tracker::reset();
         Tracker::start("gkr");
         {
             update_add();
             let summary_1 = Tracker::summary();
-            assert_eq!(&summary_1.values.add, &1);
-            assert_eq!(&summary_1.children.as_ref().unwrap()[0].values.add, &1);
+            assert_eq!(&summary_1.values[Op::Add], &1);
+            assert_eq!(&summary_1.children.as_ref().unwrap()[0].values[Op::Add], &1);
 
             Tracker::start("sumcheck");
             let summary_2 = {
@@ -292,41 +1657,38 @@ mod tests {
                 let summary_2 = Tracker::summary();
 
                 // verify add
-                assert_eq!(&summary_2.values.add, &2);
-                assert_eq!(&summary_2.children.as_ref().unwrap()[0].values.add, &2);
+                assert_eq!(&summary_2.values[Op::Add], &2);
+                assert_eq!(&summary_2.children.as_ref().unwrap()[0].values[Op::Add], &2);
                 assert_eq!(
                     &summary_2.children.as_ref().unwrap()[0]
                         .children
                         .as_ref()
                         .unwrap()[0]
-                        .values
-                        .add,
+                        .values[Op::Add],
                     &1
                 );
 
                 // verify mul
-                assert_eq!(&summary_2.values.mul, &0);
-                assert_eq!(&summary_2.children.as_ref().unwrap()[0].values.mul, &0);
+                assert_eq!(&summary_2.values[Op::Mul], &0);
+                assert_eq!(&summary_2.children.as_ref().unwrap()[0].values[Op::Mul], &0);
                 assert_eq!(
                     &summary_2.children.as_ref().unwrap()[0]
                         .children
                         .as_ref()
                         .unwrap()[0]
-                        .values
-                        .mul,
+                        .values[Op::Mul],
                     &0
                 );
 
                 // verify inv
-                assert_eq!(&summary_2.values.inv, &1);
-                assert_eq!(&summary_2.children.as_ref().unwrap()[0].values.inv, &1);
+                assert_eq!(&summary_2.values[Op::Inv], &1);
+                assert_eq!(&summary_2.children.as_ref().unwrap()[0].values[Op::Inv], &1);
                 assert_eq!(
                     &summary_2.children.as_ref().unwrap()[0]
                         .children
                         .as_ref()
                         .unwrap()[0]
-                        .values
-                        .inv,
+                        .values[Op::Inv],
                     &1
                 );
 
@@ -342,41 +1704,38 @@ mod tests {
             let summary_4 = Tracker::summary();
 
             // verify add
-            assert_eq!(&summary_4.values.add, &2);
-            assert_eq!(&summary_4.children.as_ref().unwrap()[0].values.add, &2);
+            assert_eq!(&summary_4.values[Op::Add], &2);
+            assert_eq!(&summary_4.children.as_ref().unwrap()[0].values[Op::Add], &2);
             assert_eq!(
                 &summary_4.children.as_ref().unwrap()[0]
                     .children
                     .as_ref()
                     .unwrap()[0]
-                    .values
-                    .add,
+                    .values[Op::Add],
                 &1
             );
 
             // verify mul
-            assert_eq!(&summary_4.values.mul, &1);
-            assert_eq!(&summary_4.children.as_ref().unwrap()[0].values.mul, &1);
+            assert_eq!(&summary_4.values[Op::Mul], &1);
+            assert_eq!(&summary_4.children.as_ref().unwrap()[0].values[Op::Mul], &1);
             assert_eq!(
                 &summary_4.children.as_ref().unwrap()[0]
                     .children
                     .as_ref()
                     .unwrap()[0]
-                    .values
-                    .mul,
+                    .values[Op::Mul],
                 &0
             );
 
             // verify inv
-            assert_eq!(&summary_4.values.inv, &1);
-            assert_eq!(&summary_4.children.as_ref().unwrap()[0].values.inv, &1);
+            assert_eq!(&summary_4.values[Op::Inv], &1);
+            assert_eq!(&summary_4.children.as_ref().unwrap()[0].values[Op::Inv], &1);
             assert_eq!(
                 &summary_4.children.as_ref().unwrap()[0]
                     .children
                     .as_ref()
                     .unwrap()[0]
-                    .values
-                    .inv,
+                    .values[Op::Inv],
                 &1
             );
 
@@ -387,15 +1746,14 @@ mod tests {
                 let summary_5 = Tracker::summary();
 
                 // verify add
-                assert_eq!(&summary_5.values.add, &2);
-                assert_eq!(&summary_5.children.as_ref().unwrap()[0].values.add, &2);
+                assert_eq!(&summary_5.values[Op::Add], &2);
+                assert_eq!(&summary_5.children.as_ref().unwrap()[0].values[Op::Add], &2);
                 assert_eq!(
                     &summary_5.children.as_ref().unwrap()[0]
                         .children
                         .as_ref()
                         .unwrap()[0]
-                        .values
-                        .add,
+                        .values[Op::Add],
                     &1
                 );
                 assert_eq!(
@@ -403,21 +1761,19 @@ mod tests {
                         .children
                         .as_ref()
                         .unwrap()[1]
-                        .values
-                        .add,
+                        .values[Op::Add],
                     &0
                 );
 
                 // verify mul
-                assert_eq!(&summary_5.values.mul, &3);
-                assert_eq!(&summary_5.children.as_ref().unwrap()[0].values.mul, &3);
+                assert_eq!(&summary_5.values[Op::Mul], &3);
+                assert_eq!(&summary_5.children.as_ref().unwrap()[0].values[Op::Mul], &3);
                 assert_eq!(
                     &summary_5.children.as_ref().unwrap()[0]
                         .children
                         .as_ref()
                         .unwrap()[0]
-                        .values
-                        .mul,
+                        .values[Op::Mul],
                     &0
                 );
                 assert_eq!(
@@ -425,21 +1781,19 @@ mod tests {
                         .children
                         .as_ref()
                         .unwrap()[1]
-                        .values
-                        .mul,
+                        .values[Op::Mul],
                     &2
                 );
 
                 // verify inv
-                assert_eq!(&summary_5.values.inv, &1);
-                assert_eq!(&summary_5.children.as_ref().unwrap()[0].values.inv, &1);
+                assert_eq!(&summary_5.values[Op::Inv], &1);
+                assert_eq!(&summary_5.children.as_ref().unwrap()[0].values[Op::Inv], &1);
                 assert_eq!(
                     &summary_5.children.as_ref().unwrap()[0]
                         .children
                         .as_ref()
                         .unwrap()[0]
-                        .values
-                        .inv,
+                        .values[Op::Inv],
                     &1
                 );
                 assert_eq!(
@@ -447,8 +1801,7 @@ mod tests {
                         .children
                         .as_ref()
                         .unwrap()[1]
-                        .values
-                        .inv,
+                        .values[Op::Inv],
                     &0
                 );
 
@@ -458,15 +1811,14 @@ mod tests {
             let summary_6 = Tracker::summary();
 
             // verify add
-            assert_eq!(&summary_6.values.add, &3);
-            assert_eq!(&summary_6.children.as_ref().unwrap()[0].values.add, &3);
+            assert_eq!(&summary_6.values[Op::Add], &3);
+            assert_eq!(&summary_6.children.as_ref().unwrap()[0].values[Op::Add], &3);
             assert_eq!(
                 &summary_6.children.as_ref().unwrap()[0]
                     .children
                     .as_ref()
                     .unwrap()[0]
-                    .values
-                    .add,
+                    .values[Op::Add],
                 &1
             );
             assert_eq!(
@@ -474,21 +1826,19 @@ mod tests {
                     .children
                     .as_ref()
                     .unwrap()[1]
-                    .values
-                    .add,
+                    .values[Op::Add],
                 &1
             );
 
             // verify mul
-            assert_eq!(&summary_6.values.mul, &3);
-            assert_eq!(&summary_6.children.as_ref().unwrap()[0].values.mul, &3);
+            assert_eq!(&summary_6.values[Op::Mul], &3);
+            assert_eq!(&summary_6.children.as_ref().unwrap()[0].values[Op::Mul], &3);
             assert_eq!(
                 &summary_6.children.as_ref().unwrap()[0]
                     .children
                     .as_ref()
                     .unwrap()[0]
-                    .values
-                    .mul,
+                    .values[Op::Mul],
                 &0
             );
             assert_eq!(
@@ -496,21 +1846,19 @@ mod tests {
                     .children
                     .as_ref()
                     .unwrap()[1]
-                    .values
-                    .mul,
+                    .values[Op::Mul],
                 &2
             );
 
             // verify inv
-            assert_eq!(&summary_6.values.inv, &1);
-            assert_eq!(&summary_6.children.as_ref().unwrap()[0].values.inv, &1);
+            assert_eq!(&summary_6.values[Op::Inv], &1);
+            assert_eq!(&summary_6.children.as_ref().unwrap()[0].values[Op::Inv], &1);
             assert_eq!(
                 &summary_6.children.as_ref().unwrap()[0]
                     .children
                     .as_ref()
                     .unwrap()[0]
-                    .values
-                    .inv,
+                    .values[Op::Inv],
                 &1
             );
             assert_eq!(
@@ -518,8 +1866,7 @@ mod tests {
                     .children
                     .as_ref()
                     .unwrap()[1]
-                    .values
-                    .inv,
+                    .values[Op::Inv],
                 &0
             );
 
@@ -529,15 +1876,14 @@ mod tests {
 
         let summary_7 = Tracker::summary();
         // verify add
-        assert_eq!(&summary_7.values.add, &3);
-        assert_eq!(&summary_7.children.as_ref().unwrap()[0].values.add, &3);
+        assert_eq!(&summary_7.values[Op::Add], &3);
+        assert_eq!(&summary_7.children.as_ref().unwrap()[0].values[Op::Add], &3);
         assert_eq!(
             &summary_7.children.as_ref().unwrap()[0]
                 .children
                 .as_ref()
                 .unwrap()[0]
-                .values
-                .add,
+                .values[Op::Add],
             &1
         );
         assert_eq!(
@@ -545,21 +1891,19 @@ mod tests {
                 .children
                 .as_ref()
                 .unwrap()[1]
-                .values
-                .add,
+                .values[Op::Add],
             &1
         );
 
         // verify mul
-        assert_eq!(&summary_7.values.mul, &3);
-        assert_eq!(&summary_7.children.as_ref().unwrap()[0].values.mul, &3);
+        assert_eq!(&summary_7.values[Op::Mul], &3);
+        assert_eq!(&summary_7.children.as_ref().unwrap()[0].values[Op::Mul], &3);
         assert_eq!(
             &summary_7.children.as_ref().unwrap()[0]
                 .children
                 .as_ref()
                 .unwrap()[0]
-                .values
-                .mul,
+                .values[Op::Mul],
             &0
         );
         assert_eq!(
@@ -567,21 +1911,19 @@ mod tests {
                 .children
                 .as_ref()
                 .unwrap()[1]
-                .values
-                .mul,
+                .values[Op::Mul],
             &2
         );
 
         // verify inv
-        assert_eq!(&summary_7.values.inv, &2);
-        assert_eq!(&summary_7.children.as_ref().unwrap()[0].values.inv, &2);
+        assert_eq!(&summary_7.values[Op::Inv], &2);
+        assert_eq!(&summary_7.children.as_ref().unwrap()[0].values[Op::Inv], &2);
         assert_eq!(
             &summary_7.children.as_ref().unwrap()[0]
                 .children
                 .as_ref()
                 .unwrap()[0]
-                .values
-                .inv,
+                .values[Op::Inv],
             &1
         );
         assert_eq!(
@@ -589,14 +1931,666 @@ mod tests {
                 .children
                 .as_ref()
                 .unwrap()[1]
-                .values
-                .inv,
+                .values[Op::Inv],
             &0
         );
 
         Tracker::reset();
     }
 
+    #[test]
+    fn test_div_expansion() {
+        Tracker::reset();
+        Tracker::start("verifier");
+        update_div();
+        update_div();
+        update_mul();
+        Tracker::end();
+        update_inv();
+
+        let summary = Tracker::summary();
+        assert_eq!(summary.values[Op::Div], 2);
+        assert_eq!(summary.values[Op::Mul], 1);
+        assert_eq!(summary.values[Op::Inv], 1);
+
+        let expanded = summary.with_div_expanded();
+        assert_eq!(expanded.values[Op::Div], 0);
+        assert_eq!(expanded.values[Op::Mul], 3);
+        assert_eq!(expanded.values[Op::Inv], 3);
+        assert_eq!(expanded.children.as_ref().unwrap()[0].values[Op::Mul], 3);
+        assert_eq!(expanded.children.as_ref().unwrap()[0].values[Op::Inv], 2);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_exponentiation_expansion() {
+        Tracker::reset();
+        update_pow();
+        update_exponentiation_cost(3, 2);
+        assert_eq!(Tracker::summary().values[Op::Pow], 1);
+        assert_eq!(Tracker::summary().values[Op::Square], 0);
+        assert_eq!(Tracker::summary().values[Op::Mul], 0);
+
+        Tracker::set_expand_exponentiation(true);
+        update_pow();
+        update_exponentiation_cost(3, 2);
+        assert_eq!(Tracker::summary().values[Op::Pow], 2);
+        assert_eq!(Tracker::summary().values[Op::Square], 3);
+        assert_eq!(Tracker::summary().values[Op::Mul], 2);
+
+        // the setting follows the active tracker
+        let mut other = Tracker::new();
+        other.run(|| update_exponentiation_cost(3, 2));
+        assert!(other.report().values.is_empty());
+        update_exponentiation_cost(3, 2);
+        assert_eq!(Tracker::summary().values[Op::Square], 6);
+
+        Tracker::set_expand_exponentiation(false);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_custom_op_kinds() {
+        Tracker::reset();
+        Tracker::start("commit");
+        update(Op::Custom("poseidon_permutation"), 3);
+        update_add();
+        Tracker::end();
+        update(Op::Custom("transcript_absorb"), 2);
+        update(Op::Custom("poseidon_permutation"), 1);
+
+        let summary = Tracker::summary();
+        assert_eq!(summary.values[Op::Custom("poseidon_permutation")], 4);
+        assert_eq!(summary.values[Op::Custom("transcript_absorb")], 2);
+        assert_eq!(summary.values[Op::Add], 1);
+        assert_eq!(summary.values[Op::Mul], 0);
+        assert_eq!(
+            summary.children.as_ref().unwrap()[0].values[Op::Custom("poseidon_permutation")],
+            3
+        );
+        assert_eq!(
+            summary.children.as_ref().unwrap()[0].values.to_string(),
+            "add: 1, poseidon_permutation: 3"
+        );
+        assert_eq!(
+            summary.to_json(),
+            "{\"name\":\"Global Summary\",\"values\":{\"add\":1,\"poseidon_permutation\":4,\"transcript_absorb\":2},\"self_values\":{\"poseidon_permutation\":1,\"transcript_absorb\":2},\"children\":[{\"name\":\"commit\",\"values\":{\"add\":1,\"poseidon_permutation\":3},\"self_values\":{\"add\":1,\"poseidon_permutation\":3},\"children\":[]}]}"
+        );
+        Tracker::reset();
+    }
+
+    fn fallible_step(fail: bool) -> Result<(), ()> {
+        let _guard = Tracker::scope("step");
+        update_add();
+        if fail {
+            return Err(());
+        }
+        update_mul();
+        Ok(())
+    }
+
+    #[test]
+    fn test_scope_guard_early_return() {
+        Tracker::reset();
+        {
+            let _guard = Tracker::scope("prover");
+            assert!(fallible_step(false).is_ok());
+            assert!(fallible_step(true).is_err());
+        }
+        update_inv();
+
+        let summary = Tracker::summary();
+        let prover = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(summary.children.as_ref().unwrap().len(), 1);
+        assert_eq!(summary.values[Op::Inv], 1);
+        assert_eq!(prover.values[Op::Inv], 0);
+        assert_eq!(prover.values[Op::Add], 2);
+        assert_eq!(prover.values[Op::Mul], 1);
+        assert_eq!(prover.children.as_ref().unwrap().len(), 2);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_scope_guard_unwinds_on_panic() {
+        Tracker::reset();
+        let result = std::panic::catch_unwind(|| {
+            let _guard = Tracker::scope("prover");
+            update_add();
+            // left open by the panic, closed by the outer guard
+            Tracker::start("inner");
+            update_mul();
+            panic!("prover failed");
+        });
+        assert!(result.is_err());
+        update_inv();
+
+        let summary = Tracker::summary();
+        let prover = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(summary.values[Op::Inv], 1);
+        assert_eq!(prover.values[Op::Inv], 0);
+        assert_eq!(prover.values[Op::Mul], 1);
+        assert_eq!(prover.children.as_ref().unwrap()[0].values[Op::Mul], 1);
+
+        // the global scope is balanced again
+        assert!(std::panic::catch_unwind(Tracker::end).is_err());
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_measure() {
+        Tracker::reset();
+        Tracker::start("compare");
+        let (naive, naive_report) = Tracker::measure("naive", || {
+            update_mul();
+            update_mul();
+            update_add();
+            Tracker::start("left open");
+            update_mul();
+            4
+        });
+        let (horner, horner_report) = Tracker::measure("horner", || {
+            update_mul();
+            update_add();
+            4
+        });
+        Tracker::end();
+
+        assert_eq!(naive, horner);
+        assert_eq!(naive_report.values[Op::Mul], 3);
+        assert_eq!(
+            naive_report.children.as_ref().unwrap()[0].values[Op::Mul],
+            1
+        );
+        assert_eq!(horner_report.values[Op::Mul], 1);
+        assert_eq!(horner_report.values[Op::Add], 1);
+
+        let summary = Tracker::summary();
+        let compare = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(summary.values[Op::Mul], 4);
+        assert_eq!(compare.children.as_ref().unwrap()[0], naive_report);
+        assert_eq!(compare.children.as_ref().unwrap()[1], horner_report);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_dynamic_scope_names() {
+        Tracker::reset();
+        for round in 0..3 {
+            Tracker::start(format!("round {}", round));
+            update_add();
+            Tracker::end();
+        }
+        let layer = 7;
+        let _guard = Tracker::scope(format!("layer {layer}"));
+        drop(_guard);
+
+        let summary = Tracker::summary();
+        let children = summary.children.as_ref().unwrap();
+        assert_eq!(children[0].name(), "round 0");
+        assert_eq!(children[2].name(), "round 2");
+        assert_eq!(children[3].name(), "layer 7");
+        assert_eq!(children[1].values[Op::Add], 1);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_max_nodes() {
+        fn count_nodes(report: &Report) -> usize {
+            1 + report
+                .children
+                .iter()
+                .flatten()
+                .map(count_nodes)
+                .sum::<usize>()
+        }
+
+        Tracker::reset();
+        Tracker::set_max_nodes(Some(6));
+        Tracker::start("prove");
+        for round in 0..1000 {
+            Tracker::start(format!("round {}", round));
+            update_add();
+            Tracker::start("poly");
+            update_mul();
+            if round == 500 {
+                Tracker::start("fold");
+                update_mul();
+                Tracker::end();
+            }
+            Tracker::end();
+            Tracker::end();
+        }
+        Tracker::end();
+        Tracker::set_max_nodes(None);
+
+        let summary = Tracker::summary();
+        assert!(count_nodes(&summary) <= 7);
+        assert_eq!(summary.values[Op::Add], 1000);
+        assert_eq!(summary.values[Op::Mul], 1001);
+
+        let prove = &summary.children.as_ref().unwrap()[0];
+        let names = prove
+            .children
+            .iter()
+            .flatten()
+            .map(Report::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["round 0", "round 1", "other"]);
+        let other = &prove.children.as_ref().unwrap()[2];
+        assert_eq!(other.calls, 998);
+        assert_eq!(other.values[Op::Add], 998);
+        assert_eq!(other.self_values[Op::Mul], 999);
+        assert!(other.children.is_none());
+
+        // repeated paths keep their structure
+        Tracker::reset();
+        Tracker::set_max_nodes(Some(3));
+        for _ in 0..100 {
+            Tracker::start("round");
+            Tracker::start("poly");
+            update_add();
+            Tracker::end();
+            Tracker::end();
+        }
+        Tracker::set_max_nodes(None);
+
+        let summary = Tracker::summary();
+        assert_eq!(count_nodes(&summary), 3);
+        let round = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(round.calls, 100);
+        assert_eq!(round.children.as_ref().unwrap()[0].values[Op::Add], 100);
+    }
+
+    #[test]
+    fn test_aggregate_repeated_scopes() {
+        Tracker::reset();
+        Tracker::set_aggregate(true);
+        Tracker::start("sumcheck");
+        for round in 0..20 {
+            Tracker::start("round");
+            for _ in 0..=round % 3 {
+                update_mul();
+            }
+            if round % 2 == 0 {
+                update_add();
+            }
+            Tracker::start("poly");
+            update_add();
+            Tracker::end();
+            Tracker::end();
+        }
+        Tracker::start("final");
+        Tracker::end();
+        Tracker::end();
+
+        let summary = Tracker::summary();
+        let sumcheck = &summary.children.as_ref().unwrap()[0];
+        let children = sumcheck.children.as_ref().unwrap();
+        assert_eq!(children.len(), 2);
+
+        let round = &children[0];
+        assert_eq!(round.name(), "round");
+        assert_eq!(round.calls, 20);
+        assert_eq!(round.values[Op::Mul], 39);
+        assert_eq!(round.min()[Op::Mul], 1);
+        assert_eq!(round.max()[Op::Mul], 3);
+        assert_eq!(round.mean(Op::Mul), 1.95);
+        assert_eq!(round.min()[Op::Add], 1);
+        assert_eq!(round.max()[Op::Add], 2);
+        assert_eq!(round.values[Op::Add], 30);
+
+        let poly = &round.children.as_ref().unwrap()[0];
+        assert_eq!(round.children.as_ref().unwrap().len(), 1);
+        assert_eq!(poly.calls, 20);
+        assert_eq!(poly.values[Op::Add], 20);
+        assert_eq!(children[1].calls, 1);
+        assert_eq!(summary.values[Op::Mul], 39);
+        assert!(format!("{}", summary).contains("calls: 20"));
+        assert!(summary.to_json().contains("\"calls\":20"));
+
+        Tracker::set_aggregate(false);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_exclusive_counts() {
+        Tracker::reset();
+        gkr_sumcheck_squence();
+        update_mul();
+
+        let summary = Tracker::summary();
+        let gkr = &summary.children.as_ref().unwrap()[0];
+        let sumcheck = &gkr.children.as_ref().unwrap()[1];
+        let poly = &sumcheck.children.as_ref().unwrap()[0];
+
+        assert_eq!(summary.values[Op::Mul], 3);
+        assert_eq!(summary.self_values[Op::Mul], 1);
+        assert_eq!(summary.self_values[Op::Add], 0);
+        assert_eq!(gkr.values[Op::Add], 2);
+        assert_eq!(gkr.self_values[Op::Add], 1);
+        assert_eq!(gkr.self_values[Op::Mul], 0);
+        assert_eq!(sumcheck.values[Op::Mul], 1);
+        assert_eq!(sumcheck.self_values[Op::Mul], 0);
+        assert_eq!(sumcheck.self_values[Op::Inv], 1);
+        assert_eq!(poly.self_values, poly.values);
+        assert!(format!("{}", summary).contains("self: add: 1"));
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_context_of_ended_scope() {
+        Tracker::reset();
+        Tracker::start("prover");
+        Tracker::start("commit");
+        let context = Tracker::context();
+        std::thread::scope(|scope| {
+            scope.spawn(|| context.run(update_add));
+        });
+        Tracker::end();
+        // a sibling at the same depth as the ended scope
+        Tracker::start("open");
+        context.join();
+        Tracker::end();
+        Tracker::end();
+
+        let summary = Tracker::summary();
+        let prover = &summary.children.as_ref().unwrap()[0];
+        let children = prover.children.as_ref().unwrap();
+        assert_eq!(children[1].name(), "open");
+        assert_eq!(children[1].values[Op::Add], 0);
+        assert_eq!(prover.self_values[Op::Add], 1);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_entered_context() {
+        Tracker::reset();
+        Tracker::start("commit");
+        let context = Tracker::context();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    // one tracker for the whole batch
+                    let entered = context.enter();
+                    for _ in 0..100 {
+                        let _guard = Tracker::scope("leaf");
+                        update_mul();
+                    }
+                    drop(entered);
+                    assert!(Tracker::summary().values.is_empty());
+                });
+            }
+        });
+        context.join();
+        Tracker::end();
+
+        let summary = Tracker::summary();
+        let commit = &summary.children.as_ref().unwrap()[0];
+        let leaf = &commit.children.as_ref().unwrap()[0];
+        assert_eq!(commit.values[Op::Mul], 400);
+        assert_eq!(leaf.calls, 400);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_cross_thread_context() {
+        Tracker::reset();
+        Tracker::start("prover");
+        update_inv();
+        Tracker::start("commit");
+        let context = Tracker::context();
+        std::thread::scope(|scope| {
+            for worker in 0..4 {
+                let context = &context;
+                scope.spawn(move || {
+                    context.run(|| {
+                        update_add();
+                        Tracker::start("leaf");
+                        update_mul();
+                        if worker % 2 == 0 {
+                            update_mul();
+                        }
+                        Tracker::end();
+                        // left open, closed when the work returns
+                        Tracker::start("hash");
+                        update(Op::Custom("poseidon_permutation"), 1);
+                    })
+                });
+            }
+        });
+        // work on the calling thread is collected the same way
+        context.run(update_add);
+        Tracker::start("open");
+        update_sub();
+        // joined while a deeper scope is open, still lands in "commit"
+        context.join();
+        Tracker::end();
+        Tracker::end();
+        Tracker::end();
+
+        let summary = Tracker::summary();
+        let prover = &summary.children.as_ref().unwrap()[0];
+        let commit = &prover.children.as_ref().unwrap()[0];
+        let children = commit.children.as_ref().unwrap();
+
+        assert_eq!(summary.values[Op::Add], 5);
+        assert_eq!(summary.values[Op::Mul], 6);
+        assert_eq!(prover.self_values[Op::Add], 0);
+        assert_eq!(commit.values[Op::Add], 5);
+        assert_eq!(commit.self_values[Op::Add], 5);
+        assert_eq!(commit.values[Op::Sub], 1);
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[2].name(), "open");
+        assert_eq!(children[0].name(), "hash");
+        assert_eq!(children[0].calls, 4);
+        assert_eq!(children[0].values[Op::Custom("poseidon_permutation")], 4);
+        assert_eq!(children[1].name(), "leaf");
+        assert_eq!(children[1].values[Op::Mul], 6);
+        assert_eq!(children[1].min()[Op::Mul], 1);
+        assert_eq!(children[1].max()[Op::Mul], 2);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_explicit_tracker_instances() {
+        Tracker::reset();
+        let mut first = Tracker::new();
+        let mut second = Tracker::new();
+
+        update_inv();
+        first.run(|| {
+            Tracker::start("protocol a");
+            update_add();
+        });
+        second.run(|| {
+            Tracker::start("protocol b");
+            update_mul();
+            Tracker::end();
+        });
+        {
+            let _entered = first.enter();
+            update_add();
+            Tracker::end();
+            // nested instances restore the outer one
+            second.run(update_mul);
+            update_add();
+        }
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            second.run(|| {
+                update_mul();
+                panic!("protocol failed");
+            })
+        }));
+        assert!(result.is_err());
+
+        let first_report = first.report();
+        assert_eq!(first_report.values[Op::Add], 3);
+        assert_eq!(first_report.values[Op::Mul], 0);
+        assert_eq!(first_report.self_values[Op::Add], 1);
+        assert_eq!(
+            first_report.children.as_ref().unwrap()[0].name(),
+            "protocol a"
+        );
+
+        let second_report = second.report();
+        assert_eq!(second_report.values[Op::Mul], 3);
+        assert_eq!(second_report.values[Op::Add], 0);
+        assert_eq!(
+            second_report.children.as_ref().unwrap()[0].name(),
+            "protocol b"
+        );
+
+        let summary = Tracker::summary();
+        assert_eq!(summary.values[Op::Inv], 1);
+        assert_eq!(summary.values[Op::Add], 0);
+        assert!(summary.children.is_none());
+
+        first.clear();
+        assert!(first.report().values.is_empty());
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_snapshot_delta() {
+        Tracker::reset();
+        update_add();
+        Tracker::start("setup");
+        update_mul();
+
+        let snapshot = Tracker::snapshot();
+        assert!(snapshot.delta().values.is_empty());
+
+        update_mul();
+        Tracker::start("protocol");
+        update_add();
+        update(Op::Custom("transcript_absorb"), 2);
+        Tracker::end();
+        Tracker::end();
+        update_inv();
+
+        let delta = snapshot.delta();
+        assert_eq!(delta.name(), "Delta");
+        assert_eq!(delta.values[Op::Mul], 1);
+        assert_eq!(delta.values[Op::Add], 1);
+        assert_eq!(delta.values[Op::Inv], 1);
+        assert_eq!(delta.values[Op::Custom("transcript_absorb")], 2);
+        assert!(delta.children.is_none());
+
+        Tracker::reset();
+        assert!(snapshot.delta().values.is_empty());
+        for _ in 0..7 {
+            update_add();
+        }
+        // measured from the reset, not from the stale totals
+        assert_eq!(snapshot.delta().values[Op::Add], 7);
+    }
+
+    #[test]
+    fn test_pause_and_untracked() {
+        Tracker::reset();
+        let witness = untracked(|| {
+            update_add();
+            update(Op::Custom("setup"), 1);
+            Tracker::start("fixture");
+            update_mul();
+            Tracker::end();
+            42
+        });
+        assert_eq!(witness, 42);
+
+        update_mul();
+        Tracker::pause();
+        update_add();
+        Tracker::pause();
+        update_add();
+        Tracker::resume();
+        update_add();
+        Tracker::resume();
+        update_inv();
+
+        let result = std::panic::catch_unwind(|| untracked(|| panic!("setup failed")));
+        assert!(result.is_err());
+        update_inv();
+
+        let summary = Tracker::summary();
+        assert_eq!(summary.values[Op::Add], 0);
+        assert_eq!(summary.values[Op::Mul], 1);
+        assert_eq!(summary.values[Op::Inv], 2);
+        assert_eq!(summary.values[Op::Custom("setup")], 0);
+        assert!(summary.children.as_ref().unwrap()[0].values.is_empty());
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_builtin_ops() {
+        for (index, op) in BUILTIN_OPS.iter().enumerate() {
+            assert_eq!(op.builtin_index(), Some(index));
+        }
+        assert_eq!(Op::Custom("hash").builtin_index(), None);
+        assert_eq!(Op::MultiMillerLoop.name(), "multi_miller_loop");
+    }
+
+    #[test]
+    fn test_pending_counts() {
+        Tracker::reset();
+        update_add();
+        Tracker::start("commit");
+        update_mul();
+        update_mul();
+        Tracker::pause();
+        update_mul();
+        Tracker::resume();
+        Tracker::end();
+        update_add();
+        let mut other = Tracker::new();
+        other.run(update_sub);
+        update_add();
+
+        let summary = Tracker::summary();
+        let commit = &summary.children.as_ref().unwrap()[0];
+        assert_eq!(summary.self_values[Op::Add], 3);
+        assert_eq!(summary.values[Op::Sub], 0);
+        assert_eq!(commit.values[Op::Mul], 2);
+        assert_eq!(commit.values[Op::Add], 0);
+        assert_eq!(other.report().values[Op::Sub], 1);
+        Tracker::reset();
+    }
+
+    #[test]
+    fn test_disabled_by_config() {
+        if !config().enabled {
+            // a new thread has not synced its pending counts yet
+            let counted = std::thread::spawn(|| {
+                update_add();
+                update_add();
+                Tracker::summary().values[Op::Add]
+            })
+            .join()
+            .unwrap();
+            assert_eq!(counted, 0);
+            return;
+        }
+        // the configuration is read once per process, so the test runs again
+        // in a child process with tracking disabled
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tracker::tests::test_disabled_by_config"])
+            .env(FIELD_TRACKER, "off")
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_resume_without_pause() {
+        Tracker::reset();
+        Tracker::resume();
+    }
+
     #[test]
     fn test_display() {
         Tracker::reset();
@@ -1,21 +1,90 @@
+#[cfg(not(field_tracker_noop))]
 #[macro_export]
 macro_rules! Ft {
     ($field_type:ty) => {
-        $crate::Ft<{({(<$field_type as ark_ff::PrimeField>::MODULUS_BIT_SIZE + 64 - 1) / 64}) as usize}, $field_type>
+        $crate::Ft<{(<$field_type as ark_ff::PrimeField>::MODULUS_BIT_SIZE.div_ceil(64)) as usize}, $field_type>
     };
 }
 
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! Ft {
+    ($field_type:ty) => {
+        $field_type
+    };
+}
+
+#[cfg(not(field_tracker_noop))]
+#[macro_export]
+macro_rules! FtExt {
+    ($field_type:ty) => {
+        $crate::extension::FtExt<{(<<$field_type as ark_ff::Field>::BasePrimeField as ark_ff::PrimeField>::MODULUS_BIT_SIZE.div_ceil(64)) as usize}, $field_type>
+    };
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! FtExt {
+    ($field_type:ty) => {
+        $field_type
+    };
+}
+
+#[cfg(not(field_tracker_noop))]
+#[macro_export]
+macro_rules! Pt {
+    ($group_type:ty) => {
+        $crate::curve::Pt<$group_type>
+    };
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! Pt {
+    ($group_type:ty) => {
+        $group_type
+    };
+}
+
+#[cfg(not(field_tracker_noop))]
+#[macro_export]
+macro_rules! Pe {
+    ($pairing_type:ty) => {
+        $crate::pairing::Pe<$pairing_type>
+    };
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! Pe {
+    ($pairing_type:ty) => {
+        $pairing_type
+    };
+}
+
+#[cfg(not(field_tracker_noop))]
 #[macro_export]
 macro_rules! start_tscope {
-    ($scope_name:expr) => {
-        if std::any::type_name_of_val($scope_name).contains("str") {
-            $crate::tracker::Tracker::start($scope_name)
+    ($scope_name:literal) => {
+        $crate::tracker::Tracker::start($crate::tracker::scope_name(format_args!($scope_name)))
+    };
+    ($scope_name:expr) => {{
+        let scope_name = $scope_name;
+        if std::any::type_name_of_val(&scope_name).contains("str") {
+            $crate::tracker::Tracker::start(scope_name)
         } else {
             panic!("start_scope requires scope name (string)")
         }
+    }};
+    ($scope_name:literal, $($arg:tt)*) => {
+        $crate::tracker::Tracker::start($crate::tracker::scope_name(format_args!(
+            $scope_name,
+            $($arg)*
+        )))
     };
 }
 
+#[cfg(not(field_tracker_noop))]
 #[macro_export]
 macro_rules! end_tscope {
     () => {
@@ -23,10 +92,96 @@ macro_rules! end_tscope {
     };
 }
 
+#[cfg(not(field_tracker_noop))]
+#[macro_export]
+macro_rules! tscope {
+    ($scope_name:literal) => {
+        $crate::tracker::Tracker::scope($crate::tracker::scope_name(format_args!($scope_name)))
+    };
+    ($scope_name:expr) => {
+        $crate::tracker::Tracker::scope($scope_name)
+    };
+    ($scope_name:literal, $($arg:tt)*) => {
+        $crate::tracker::Tracker::scope($crate::tracker::scope_name(format_args!(
+            $scope_name,
+            $($arg)*
+        )))
+    };
+}
+
+#[cfg(not(field_tracker_noop))]
+#[macro_export]
+macro_rules! count {
+    ($counter_name:expr) => {
+        $crate::count!($counter_name, 1)
+    };
+    ($counter_name:expr, $count:expr) => {
+        $crate::tracker::update($crate::tracker::Op::Custom($counter_name), $count)
+    };
+}
+
+#[cfg(not(field_tracker_noop))]
+#[macro_export]
+macro_rules! print_summary {
+    () => {
+        $crate::config::print_summary()
+    };
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! start_tscope {
+    ($scope_name:literal) => {
+        ()
+    };
+    ($scope_name:expr) => {{
+        let _ = $scope_name;
+    }};
+    ($scope_name:literal, $($arg:tt)*) => {{
+        let _ = format_args!($scope_name, $($arg)*);
+    }};
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! end_tscope {
+    () => {
+        ()
+    };
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! tscope {
+    ($scope_name:literal) => {
+        $crate::tracker::ScopeGuard::noop()
+    };
+    ($scope_name:expr) => {{
+        let _ = $scope_name;
+        $crate::tracker::ScopeGuard::noop()
+    }};
+    ($scope_name:literal, $($arg:tt)*) => {{
+        let _ = format_args!($scope_name, $($arg)*);
+        $crate::tracker::ScopeGuard::noop()
+    }};
+}
+
+#[cfg(field_tracker_noop)]
+#[macro_export]
+macro_rules! count {
+    ($counter_name:expr) => {{
+        let _ = $counter_name;
+    }};
+    ($counter_name:expr, $count:expr) => {{
+        let _ = ($counter_name, $count);
+    }};
+}
+
+#[cfg(field_tracker_noop)]
 #[macro_export]
 macro_rules! print_summary {
     () => {
-        println!("{}", $crate::tracker::Tracker::summary())
+        ()
     };
 }
 